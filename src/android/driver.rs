@@ -0,0 +1,39 @@
+use appium_client::{capabilities::android::AndroidCapabilities, Client};
+use async_trait::async_trait;
+use fantoccini::{elements::Element, error::CmdError};
+
+use crate::android::get_android_element_by;
+use crate::common::{find_once_by, Driver, Platform, Selector};
+
+/// [`Driver`] implementation backed by a UiAutomator2 session
+pub struct AndroidDriver {
+    client: Client<AndroidCapabilities>,
+}
+
+impl AndroidDriver {
+    pub fn new(client: Client<AndroidCapabilities>) -> Self {
+        AndroidDriver { client }
+    }
+}
+
+#[async_trait]
+impl Driver for AndroidDriver {
+    fn platform(&self) -> Platform {
+        Platform::Android
+    }
+
+    fn client(&self) -> &fantoccini::Client {
+        &self.client
+    }
+
+    async fn find_once(&self, selector: &Selector) -> Result<Option<Element>, CmdError> {
+        find_once_by(&self.client, get_android_element_by(selector.clone())).await
+    }
+
+    async fn type_text(&self, text: &str) -> Result<(), CmdError> {
+        self.client
+            .execute("mobile: type", vec![serde_json::json!({ "text": text })])
+            .await
+            .map(|_| ())
+    }
+}
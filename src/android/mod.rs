@@ -1,4 +1,4 @@
-mod steps;
+mod driver;
 
 #[cfg(test)]
 mod mod_test;
@@ -7,17 +7,19 @@ use std::collections::HashMap;
 
 use appium_client::capabilities::android::AndroidCapabilities;
 use appium_client::find::By;
-use appium_client::ClientBuilder;
 use colored::Colorize;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-
 use appium_client::capabilities::{AppCapable, AppiumCapability};
-use steps::execute_android_steps;
+pub use driver::AndroidDriver;
 
 use crate::common::tags::*;
 use crate::common::*;
+use crate::config::RunOptions;
+use crate::error::Result;
+use crate::logger::Logger;
+use crate::reporting::RunResult;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -54,67 +56,17 @@ pub enum AndroidAction {
     Pause(u64),
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
-#[serde(untagged)]
-#[allow(non_snake_case)]
-pub enum AndroidElementSelector {
-    Hint {
-        hint: String,
-    },
-    AccessibilityId {
-        accessibilityId: String,
-    },
-    Text {
-        text: String,
-    },
-    Xpath {
-        xpath: String,
-    },
-    ClassName {
-        className: String,
-        instance: Option<u32>,
-    },
-    Id {
-        id: String,
-    },
-    IdWithIndex {
-        id: String,
-        index: u32,
-    },
-    Description {
-        description: String,
-    },
-    Index {
-        index: u32,
-    },
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
-struct StepFile {
-    steps: Vec<AndroidStep>,
-}
+/// Android code addresses elements through the shared driver selector
+pub type AndroidElementSelector = Selector;
 
 pub fn set_custom_capabilities_android(
     caps: &mut AndroidCapabilities,
     custom_caps: Vec<CustomCapability>,
 ) {
-    for custom_capability in custom_caps.clone() {
-        match custom_capability.value {
-            CustomCapabilityValue::BooleanValue(value) => {
-                caps.set_bool(&custom_capability.key, value)
-            },
-            CustomCapabilityValue::StringValue(value) => {
-                caps.set_str(&custom_capability.key, &value)
-            },
-            CustomCapabilityValue::NumberValue(value) => {
-                // Handle number values appropriately
-                // Convert the number to a string
-                let num_str = value.to_string();
-                caps.set_str(&custom_capability.key, &num_str);
-            },
-            CustomCapabilityValue::NullValue => {
-                // Handle null values if needed
-            }
+    for custom_capability in custom_caps {
+        let value = custom_capability.value.to_value();
+        if !value.is_null() {
+            caps.insert(custom_capability.key, value);
         }
     }
 }
@@ -135,6 +87,10 @@ pub fn get_android_element_by(selector: AndroidElementSelector) -> By {
             "new UiSelector().descriptionMatches(\"{}\");",
             description
         )),
+        AndroidElementSelector::Tooltip { tooltip } => By::uiautomator(&format!(
+            "new UiSelector().descriptionMatches(\"{}\");",
+            tooltip
+        )),
         AndroidElementSelector::Hint { hint } => {
             By::xpath(&format!("//android.widget.EditText[@hint=\"{}\"]", hint))
         }
@@ -163,59 +119,33 @@ pub fn get_android_element_by(selector: AndroidElementSelector) -> By {
 
 pub async fn launch_android_main(
     capabilities: &HashMap<String, Value>,
-    steps: Vec<Step>,
-) -> Result<(usize, String), Box<dyn std::error::Error>> {
+    steps: Vec<FlowStep>,
+    options: &RunOptions,
+) -> Result<RunResult> {
     // Configure the Appium driver
     let mut caps = AndroidCapabilities::new_uiautomator();
 
-    let app_path = capabilities
-        .get("appium:app")
-        .expect("No app path found")
-        .as_str()
-        .unwrap();
-    caps.app(&app_path);
-
-    caps.platform_version(
-        &capabilities
-            .get("platformVersion")
-            .expect("No platform version found")
-            .as_str()
-            .unwrap(),
-    );
-
-    for (key, value) in capabilities.iter() {
-        match key.as_str() {
-            "app" | "platformVersion" => continue,
-            _ => match value {
-                Value::String(value) => {
-                    caps.set_str(key, value);
-                }
-                Value::Bool(value) => {
-                    caps.set_bool(key, *value);
-                }
-                _ => {
-                    eprintln!("{} Invalid value for key: {}", error_tag(), key);
-                    std::process::exit(1);
-                }
-            },
-        }
-    }
-
-    println!(
-        "{} App path: {}",
-        info_tag(),
-        &capabilities.get("appium:app").unwrap().to_string().blue()
-    );
-    println!("⏳ Launching android app");
-    let client = ClientBuilder::native(caps)
-        .connect("http://localhost:4723/")
-        .await
-        .unwrap_or_else(|e| {
-            println!("{} Failed to connect to Appium: {}", error_tag(), e);
-            std::process::exit(1);
-        });
-    println!("✓ Android app launched successfully");
-
-    let (steps_count, report) = execute_android_steps(&client, steps).await;
-    Ok((steps_count, report))
+    let app_path = required_capability(capabilities, &["appium:app", "app"])?;
+    let platform_version =
+        required_capability(capabilities, &["appium:platformVersion", "platformVersion"])?;
+    set_capabilities(&mut caps, capabilities, &["app", "platformVersion"]);
+    caps.app(app_path);
+    caps.platform_version(platform_version);
+
+    Logger::progress(format!("{} App path: {}", info_tag(), app_path.blue()));
+    Logger::progress("⏳ Launching android app");
+    let client = connect(caps, &options.server).await?;
+    Logger::progress("✓ Android app launched successfully");
+
+    let driver = AndroidDriver::new(client);
+    let mut run = execute_steps(
+        &driver,
+        steps,
+        &options.execution,
+        &ExecutionContext::default(),
+    )
+    .await;
+    disconnect(driver.client()).await;
+    run.device = device_name(capabilities);
+    Ok(run)
 }
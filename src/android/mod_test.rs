@@ -5,7 +5,7 @@ mod tests {
     use crate::common::CustomCapabilityValue;
     use crate::android::set_custom_capabilities_android;
     use appium_client::capabilities::android::AndroidCapabilities;
-    use appium_client::find::By;
+    use serde_json::json;
 
     #[test]
     fn test_android_element_selector_index() {
@@ -164,8 +164,26 @@ mod tests {
         ];
         
         set_custom_capabilities_android(&mut caps, custom_caps);
-        
-        // We can't directly check the capabilities, but we can verify the code doesn't panic
+
+        assert_eq!(caps.get("newCommandTimeout"), Some(&json!(60)));
+    }
+
+    #[test]
+    fn test_set_custom_capabilities_nested() {
+        let mut caps = AndroidCapabilities::new_uiautomator();
+        let custom_caps: Vec<CustomCapability> = serde_yaml::from_str(
+            "- key: appium:otherApps\n  value: [a.apk, b.apk]\n\
+             - key: appium:chromeOptions\n  value:\n    args: [--headless]\n",
+        )
+        .expect("custom caps should parse");
+
+        set_custom_capabilities_android(&mut caps, custom_caps);
+
+        assert_eq!(caps.get("appium:otherApps"), Some(&json!(["a.apk", "b.apk"])));
+        assert_eq!(
+            caps.get("appium:chromeOptions"),
+            Some(&json!({ "args": ["--headless"] }))
+        );
     }
 
     #[test]
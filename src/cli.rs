@@ -0,0 +1,269 @@
+//! Command line interface of the `rp` binary
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::common::{parse_assignment, Env, ExecutionOptions, ServerOptions, ServerOverrides};
+use crate::config::RunOptions;
+use crate::error::Result;
+use crate::logger::Verbosity;
+use crate::project::ProfileSettings;
+use crate::reporting::{ReportFormat, DEFAULT_REPORT_DIR};
+
+/// Subcommand names, used to keep `rp <caps_file> <test_file>` working
+const SUBCOMMANDS: [&str; 7] = [
+    "run", "validate", "list", "graph", "report", "doctor", "help",
+];
+
+#[derive(Debug, Parser)]
+#[command(name = "rp", version, about = "Run Maestro-style flows on Appium")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+
+    /// Print debug output
+    #[arg(short, long, global = true, conflicts_with = "quiet")]
+    pub verbose: bool,
+
+    /// Only print warnings, errors and the final summary
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run a test file against an Appium server
+    Run(RunArgs),
+    /// Check that test files (and optionally a caps file) parse and resolve
+    Validate {
+        /// Test files to check
+        #[arg(required = true)]
+        test_files: Vec<PathBuf>,
+        /// Caps file to check as well
+        #[arg(long)]
+        caps: Option<PathBuf>,
+    },
+    /// Print the flattened steps of a test file
+    List { test_file: PathBuf },
+    /// Print the Mermaid graph of the runFlow tree of a test file
+    Graph { test_file: PathBuf },
+    /// Render a saved JSON report in other formats
+    Report {
+        /// JSON report written by `run --report json`
+        json_report: PathBuf,
+        #[command(flatten)]
+        output: ReportArgs,
+    },
+    /// Check the Appium server and, optionally, a caps file
+    Doctor {
+        /// Caps file to check
+        caps_file: Option<PathBuf>,
+        #[command(flatten)]
+        server: ServerArgs,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Appium capabilities file, which can be left to the profile, then the test file or
+    /// directory of flows to run
+    #[arg(value_name = "[CAPS_FILE] TEST_FILE|DIR", required = true, num_args = 1..=2)]
+    pub files: Vec<PathBuf>,
+
+    /// Profile of rust_pilot.toml to use [env: RUST_PILOT_PROFILE]
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    #[command(flatten)]
+    pub server: ServerArgs,
+
+    #[command(flatten)]
+    pub output: ReportArgs,
+
+    /// Also print the JSON report to stdout, moving the log to stderr
+    #[arg(long)]
+    pub json_stdout: bool,
+
+    /// Only run flows that have one of these tags
+    #[arg(long = "include-tags", value_delimiter = ',')]
+    pub include_tags: Vec<String>,
+
+    /// Skip flows that have any of these tags
+    #[arg(long = "exclude-tags", value_delimiter = ',')]
+    pub exclude_tags: Vec<String>,
+
+    /// Flow variable, overriding the flows' env blocks (repeatable)
+    #[arg(short = 'e', long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+
+    /// Seconds to wait for elements before failing a step [default: 30]
+    #[arg(long, value_name = "SECONDS")]
+    pub timeout: Option<u64>,
+
+    /// Milliseconds between two checks while waiting for elements [default: 250]
+    #[arg(long, value_name = "MILLISECONDS")]
+    pub poll_interval: Option<u64>,
+
+    /// Times a failing lookup or assertion step is retried before the run fails [default: 0]
+    #[arg(long)]
+    pub retries: Option<u32>,
+
+    /// Attach a screenshot of every passing step to the report, not only of the failing one
+    #[arg(long)]
+    pub capture_steps: bool,
+}
+
+/// Appium server flags; unset ones fall back to `RUST_PILOT_*` env vars, then the caps file
+#[derive(Debug, Args)]
+pub struct ServerArgs {
+    /// Appium server URL [default: http://localhost:4723/]
+    #[arg(long)]
+    pub appium_url: Option<String>,
+
+    /// Path the server is mounted on, e.g. /wd/hub for Appium 1
+    #[arg(long)]
+    pub appium_base_path: Option<String>,
+
+    /// Seconds to wait for a session to be created [default: 120]
+    #[arg(long, value_name = "SECONDS")]
+    pub connect_timeout: Option<u64>,
+
+    /// Seconds Appium waits for the next command before ending the session
+    #[arg(long, value_name = "SECONDS")]
+    pub new_command_timeout: Option<u64>,
+
+    /// Times an unreachable server is retried, with doubling waits [default: 2]
+    #[arg(long)]
+    pub connect_retries: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Directory reports are written to [default: reports]
+    #[arg(long)]
+    pub report_dir: Option<String>,
+
+    /// Report formats: md, json, junit, html (repeatable or comma-separated) [default: md]
+    #[arg(long = "report", value_delimiter = ',')]
+    pub formats: Vec<String>,
+}
+
+impl Cli {
+    /// Parses the process arguments, treating `rp <caps_file> <test_file>` as `rp run ...`
+    pub fn parse_args() -> Self {
+        Cli::parse_from(normalize_args(std::env::args_os()))
+    }
+
+    pub fn verbosity(&self) -> Verbosity {
+        if self.verbose {
+            Verbosity::Verbose
+        } else if self.quiet {
+            Verbosity::Quiet
+        } else {
+            Verbosity::Normal
+        }
+    }
+}
+
+impl RunArgs {
+    /// Caps file given on the command line, if any
+    pub fn caps_file(&self) -> Option<&Path> {
+        match self.files.as_slice() {
+            [caps_file, _] => Some(caps_file),
+            _ => None,
+        }
+    }
+
+    pub fn test_file(&self) -> &Path {
+        self.files.last().expect("clap requires a test file")
+    }
+
+    /// Variables given with `-e`
+    pub fn variables(&self) -> Result<Env> {
+        self.env
+            .iter()
+            .map(|assignment| parse_assignment(assignment))
+            .collect()
+    }
+
+    /// Fills the settings not given on the command line from a `rust_pilot.toml` profile
+    pub fn apply_profile(&mut self, profile: &ProfileSettings) {
+        if self.output.report_dir.is_none() {
+            self.output.report_dir = profile.report_dir.clone();
+        }
+        if self.output.formats.is_empty() {
+            self.output.formats = profile.report.clone().unwrap_or_default();
+        }
+        self.timeout = self.timeout.or(profile.timeout);
+        self.poll_interval = self.poll_interval.or(profile.poll_interval);
+        self.retries = self.retries.or(profile.retries);
+        self.capture_steps |= profile.capture_steps.unwrap_or_default();
+    }
+
+    /// Options of the run, with `server` already resolved from [`ServerArgs`]
+    pub fn run_options(&self, server: ServerOptions) -> RunOptions {
+        RunOptions {
+            server,
+            execution: ExecutionOptions {
+                timeout: self.timeout.map(Duration::from_secs),
+                poll_interval: self.poll_interval.map(Duration::from_millis),
+                retries: self.retries.unwrap_or_default(),
+                report_dir: Some(PathBuf::from(self.output.report_dir())),
+                capture_steps: self.capture_steps,
+            },
+        }
+    }
+}
+
+impl ServerArgs {
+    pub fn overrides(&self) -> ServerOverrides {
+        ServerOverrides {
+            url: self.appium_url.clone(),
+            base_path: self.appium_base_path.clone(),
+            connect_timeout: self.connect_timeout,
+            new_command_timeout: self.new_command_timeout,
+            connect_retries: self.connect_retries,
+        }
+    }
+}
+
+impl ReportArgs {
+    pub fn report_dir(&self) -> &str {
+        self.report_dir.as_deref().unwrap_or(DEFAULT_REPORT_DIR)
+    }
+
+    /// Requested report formats, without duplicates; Markdown when none is requested
+    pub fn report_formats(&self) -> Result<Vec<ReportFormat>> {
+        if self.formats.is_empty() {
+            return Ok(vec![ReportFormat::Markdown]);
+        }
+        let mut formats = Vec::new();
+        for name in &self.formats {
+            let format = ReportFormat::parse(name)?;
+            if !formats.contains(&format) {
+                formats.push(format);
+            }
+        }
+        Ok(formats)
+    }
+}
+
+/// Inserts the `run` subcommand when the first argument isn't a subcommand or flag
+pub fn normalize_args<I, T>(args: I) -> Vec<OsString>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let mut args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let needs_run = args.get(1).is_some_and(|first| {
+        let first = first.to_string_lossy();
+        !first.starts_with('-') && !SUBCOMMANDS.contains(&first.as_ref())
+    });
+    if needs_run {
+        args.insert(1, OsString::from("run"));
+    }
+    args
+}
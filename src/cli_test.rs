@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::time::Duration;
+
+    use clap::Parser;
+
+    use crate::cli::{normalize_args, Cli, Command};
+    use crate::common::{ServerOptions, ServerOverrides};
+    use crate::logger::Verbosity;
+    use crate::project::ProfileSettings;
+    use crate::reporting::ReportFormat;
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(normalize_args(args.iter().copied())).expect("arguments should parse")
+    }
+
+    #[test]
+    fn test_legacy_positional_arguments_run() {
+        let cli = parse(&["rp", "caps.json", "flow.yml"]);
+        assert_eq!(cli.verbosity(), Verbosity::Normal);
+
+        let Command::Run(args) = cli.command else {
+            panic!("expected the run subcommand");
+        };
+        assert_eq!(args.caps_file(), Some(Path::new("caps.json")));
+        assert_eq!(args.test_file(), Path::new("flow.yml"));
+        assert_eq!(args.server.appium_url, None);
+        assert_eq!(args.server.overrides(), ServerOverrides::default());
+        assert_eq!(args.output.report_dir(), "reports");
+        assert_eq!(
+            args.output.report_formats().unwrap(),
+            vec![ReportFormat::Markdown]
+        );
+    }
+
+    #[test]
+    fn test_run_flags() {
+        let cli = parse(&[
+            "rp",
+            "run",
+            "caps.json",
+            "flow.yml",
+            "--appium-url",
+            "http://grid:4444",
+            "--appium-base-path",
+            "/wd/hub",
+            "--connect-timeout",
+            "30",
+            "--new-command-timeout",
+            "600",
+            "--connect-retries",
+            "4",
+            "--report",
+            "junit,html",
+            "--report",
+            "junit",
+            "--report-dir",
+            "out",
+            "--include-tags",
+            "smoke,login",
+            "--exclude-tags",
+            "slow",
+            "--timeout",
+            "10",
+            "--poll-interval",
+            "100",
+            "--retries",
+            "2",
+            "-q",
+        ]);
+
+        assert_eq!(cli.verbosity(), Verbosity::Quiet);
+        let Command::Run(args) = cli.command else {
+            panic!("expected the run subcommand");
+        };
+        assert_eq!(
+            args.output.report_formats().unwrap(),
+            vec![ReportFormat::Junit, ReportFormat::Html]
+        );
+        assert_eq!(args.output.report_dir(), "out");
+        assert_eq!(args.include_tags, vec!["smoke", "login"]);
+        assert_eq!(args.exclude_tags, vec!["slow"]);
+
+        let server =
+            ServerOptions::resolve(&args.server.overrides(), &HashMap::new(), |_| None).unwrap();
+        assert_eq!(server.endpoint(), "http://grid:4444/wd/hub/");
+        assert_eq!(server.connect_timeout, Duration::from_secs(30));
+        assert_eq!(server.new_command_timeout, Some(600));
+        assert_eq!(server.connect_retries, 4);
+
+        let options = args.run_options(server);
+        assert_eq!(options.execution.timeout, Some(Duration::from_secs(10)));
+        assert_eq!(
+            options.execution.poll_interval,
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(options.execution.retries, 2);
+    }
+
+    #[test]
+    fn test_run_with_profile() {
+        let cli = parse(&[
+            "rp",
+            "flow.yml",
+            "--profile",
+            "staging-pixel",
+            "--retries",
+            "3",
+        ]);
+        let Command::Run(mut args) = cli.command else {
+            panic!("expected the run subcommand");
+        };
+        assert_eq!(args.caps_file(), None);
+        assert_eq!(args.test_file(), Path::new("flow.yml"));
+        assert_eq!(args.profile.as_deref(), Some("staging-pixel"));
+
+        args.apply_profile(&ProfileSettings {
+            report: Some(vec!["junit".to_string()]),
+            report_dir: Some("ci-reports".to_string()),
+            timeout: Some(20),
+            poll_interval: Some(500),
+            retries: Some(1),
+            capture_steps: Some(true),
+            ..ProfileSettings::default()
+        });
+        assert_eq!(
+            args.output.report_formats().unwrap(),
+            vec![ReportFormat::Junit]
+        );
+        assert_eq!(args.output.report_dir(), "ci-reports");
+        assert_eq!(args.timeout, Some(20));
+        assert_eq!(args.poll_interval, Some(500));
+        assert!(args.capture_steps);
+        // The command line wins over the profile
+        assert_eq!(args.retries, Some(3));
+    }
+
+    #[test]
+    fn test_subcommands() {
+        assert!(matches!(
+            parse(&["rp", "validate", "a.yml", "b.yml", "--caps", "caps.json"]).command,
+            Command::Validate { test_files, caps: Some(_) } if test_files.len() == 2
+        ));
+        assert!(matches!(
+            parse(&["rp", "list", "a.yml"]).command,
+            Command::List { .. }
+        ));
+        assert!(matches!(
+            parse(&["rp", "graph", "a.yml"]).command,
+            Command::Graph { .. }
+        ));
+        assert!(matches!(
+            parse(&["rp", "report", "REPORT.json", "--report", "html"]).command,
+            Command::Report { .. }
+        ));
+        assert!(matches!(
+            parse(&["rp", "-v", "doctor"]).command,
+            Command::Doctor {
+                caps_file: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        assert!(Cli::try_parse_from(normalize_args(["rp", "run"])).is_err());
+        assert!(
+            Cli::try_parse_from(normalize_args(["rp", "caps.json", "a.yml", "b.yml"])).is_err()
+        );
+        assert!(
+            Cli::try_parse_from(["rp", "run", "caps.json", "flow.yml", "--retries", "many"])
+                .is_err()
+        );
+        assert!(Cli::try_parse_from(["rp", "doctor", "-v", "-q"]).is_err());
+    }
+}
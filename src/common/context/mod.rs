@@ -0,0 +1,141 @@
+//! Execution context passed to every step
+//!
+//! Carries what a step needs to know about where it runs: the `runFlow` depth
+//! and file of its flow, the flow's variables, and where its output goes. Log
+//! indentation follows the depth of the step being run, not of the last flow
+//! that was parsed.
+
+#[cfg(test)]
+mod test;
+
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::common::steps::FlowStep;
+use crate::common::variables::Variables;
+use crate::logger::Logger;
+use crate::secrets;
+
+/// Kind of a line of step output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Step,
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// Destination of step output
+pub trait OutputSink: Send + Sync {
+    fn log(&self, level: LogLevel, message: &str, indent_level: usize);
+}
+
+/// Sends step output to the [`Logger`]
+pub struct LoggerSink;
+
+impl OutputSink for LoggerSink {
+    fn log(&self, level: LogLevel, message: &str, indent_level: usize) {
+        match level {
+            LogLevel::Step => Logger::step_with_indent(message, indent_level),
+            LogLevel::Info => Logger::info_with_indent(message, indent_level),
+            LogLevel::Success => Logger::success_with_indent(message, indent_level),
+            LogLevel::Warning => Logger::warning_with_indent(message, indent_level),
+            LogLevel::Error => Logger::error_with_indent(message, indent_level),
+        }
+    }
+}
+
+/// Where and with what a step runs
+#[derive(Clone)]
+pub struct ExecutionContext {
+    /// `runFlow` nesting of the current step; 0 for the top-level flow
+    pub depth: usize,
+    /// Flow file the current step was declared in, when known
+    pub flow: Option<String>,
+    pub variables: Arc<Variables>,
+    /// App the current flow targets, from its `appId` header
+    pub app_id: Option<String>,
+    sink: Arc<dyn OutputSink>,
+}
+
+impl Default for ExecutionContext {
+    fn default() -> Self {
+        ExecutionContext::new(Arc::new(LoggerSink))
+    }
+}
+
+impl ExecutionContext {
+    pub fn new(sink: Arc<dyn OutputSink>) -> Self {
+        ExecutionContext {
+            depth: 0,
+            flow: None,
+            variables: Arc::new(Variables::default()),
+            app_id: None,
+            sink,
+        }
+    }
+
+    /// Context of a flattened step, at the depth and in the flow it was declared in
+    pub fn for_step(&self, flow_step: &FlowStep) -> Self {
+        let mut context = self.clone();
+        context.variables = flow_step.variables.clone();
+        context.app_id = flow_step.app_id.clone();
+        if let Some(source) = &flow_step.source {
+            context.depth = source.depth;
+            context.flow = Some(source.file.clone());
+        }
+        context
+    }
+
+    /// Writes a line at the depth of the current step, with secrets masked
+    pub fn log<T: Display>(&self, level: LogLevel, message: T) {
+        let message = secrets::mask(&message.to_string());
+        self.sink.log(level, &message, self.depth);
+    }
+
+    pub fn info<T: Display>(&self, message: T) {
+        self.log(LogLevel::Info, message);
+    }
+
+    pub fn success<T: Display>(&self, message: T) {
+        self.log(LogLevel::Success, message);
+    }
+
+    pub fn warning<T: Display>(&self, message: T) {
+        self.log(LogLevel::Warning, message);
+    }
+
+    pub fn error<T: Display>(&self, message: T) {
+        self.log(LogLevel::Error, message);
+    }
+
+    /// Announces an action, returning the handle that reports how it ended
+    pub fn start_spinner(&self, message: String) -> PlainLogger {
+        let message = secrets::mask(&message);
+        self.log(LogLevel::Step, &message);
+        PlainLogger {
+            message,
+            context: self.clone(),
+        }
+    }
+}
+
+/// Plain text logger for step execution
+pub struct PlainLogger {
+    pub message: String,
+    context: ExecutionContext,
+}
+
+impl PlainLogger {
+    /// Stop with a custom message
+    pub fn stop_with_symbol(&self, message: &str) {
+        self.context.info(message);
+    }
+
+    /// Stop with the start message marked as completed
+    pub fn stop(&self) {
+        self.context
+            .success(format!("{} - Completed", self.message));
+    }
+}
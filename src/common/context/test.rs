@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::common::context::{ExecutionContext, LogLevel, OutputSink};
+    use crate::common::models::{SourceLocation, Step};
+    use crate::common::steps::FlowStep;
+    use crate::common::variables::Variables;
+    use crate::secrets;
+
+    /// Keeps every line instead of printing it
+    #[derive(Default)]
+    struct MemorySink {
+        lines: Mutex<Vec<(LogLevel, String, usize)>>,
+    }
+
+    impl OutputSink for MemorySink {
+        fn log(&self, level: LogLevel, message: &str, indent_level: usize) {
+            self.lines
+                .lock()
+                .unwrap()
+                .push((level, message.to_string(), indent_level));
+        }
+    }
+
+    fn flow_step(source: Option<SourceLocation>) -> FlowStep {
+        FlowStep {
+            step: Step::RunScript {
+                runScript: "noop".to_string(),
+            },
+            variables: Arc::new(Variables::default()),
+            source,
+            app_id: None,
+        }
+    }
+
+    fn source(file: &str, depth: usize) -> SourceLocation {
+        SourceLocation {
+            file: file.to_string(),
+            line: 3,
+            column: 1,
+            include_chain: vec![file.to_string()],
+            depth,
+        }
+    }
+
+    #[test]
+    fn test_output_follows_the_depth_of_each_step() {
+        let sink = Arc::new(MemorySink::default());
+        let root = ExecutionContext::new(sink.clone());
+
+        // Steps run in flattened order, whatever flow was parsed last
+        let nested = root.for_step(&flow_step(Some(source("login.yml", 2))));
+        let top = root.for_step(&flow_step(Some(source("main.yml", 0))));
+        let unknown = nested.for_step(&flow_step(None));
+
+        let sp = nested.start_spinner("Tapping on: Login".to_string());
+        top.info("Top-level step");
+        sp.stop_with_symbol("Tapped on: Login");
+        unknown.warning("Flow-style step");
+
+        assert_eq!(nested.flow.as_deref(), Some("login.yml"));
+        assert_eq!(top.flow.as_deref(), Some("main.yml"));
+        assert_eq!(unknown.flow.as_deref(), Some("login.yml"));
+        assert_eq!(
+            *sink.lines.lock().unwrap(),
+            vec![
+                (LogLevel::Step, "Tapping on: Login".to_string(), 2),
+                (LogLevel::Info, "Top-level step".to_string(), 0),
+                (LogLevel::Info, "Tapped on: Login".to_string(), 2),
+                (LogLevel::Warning, "Flow-style step".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_output_masks_secrets() {
+        secrets::register_value("context-secret-808");
+        let sink = Arc::new(MemorySink::default());
+        let context = ExecutionContext::new(sink.clone());
+
+        let sp = context.start_spinner("Inserting context-secret-808".to_string());
+        sp.stop();
+
+        assert_eq!(sp.message, "Inserting ***");
+        assert_eq!(
+            *sink.lines.lock().unwrap(),
+            vec![
+                (LogLevel::Step, "Inserting ***".to_string(), 0),
+                (
+                    LogLevel::Success,
+                    "Inserting *** - Completed".to_string(),
+                    0
+                ),
+            ]
+        );
+    }
+}
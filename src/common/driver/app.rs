@@ -0,0 +1,218 @@
+//! App lifecycle through Appium `mobile:` commands
+//!
+//! UiAutomator2 addresses apps by package (`appId`), XCUITest by bundle id.
+//! Permissions use Maestro's names (`camera`, `location`, `all`, ...); on
+//! iOS they can only be changed on simulators.
+
+use std::collections::BTreeMap;
+
+use fantoccini::{error::CmdError, Client};
+use serde_json::{json, Map, Value};
+
+use crate::common::models::{PermissionState, Platform};
+
+/// Permissions `all` stands for on iOS
+const IOS_PERMISSIONS: [&str; 14] = [
+    "calendar",
+    "camera",
+    "contacts",
+    "health",
+    "homekit",
+    "location",
+    "medialibrary",
+    "microphone",
+    "motion",
+    "notifications",
+    "photos",
+    "reminders",
+    "siri",
+    "speech",
+];
+
+pub async fn stop_app(client: &Client, platform: Platform, app_id: &str) -> Result<(), CmdError> {
+    execute(client, "mobile: terminateApp", app_args(platform, app_id)).await
+}
+
+pub async fn clear_app(client: &Client, platform: Platform, app_id: &str) -> Result<(), CmdError> {
+    execute(client, "mobile: clearApp", app_args(platform, app_id)).await
+}
+
+pub async fn launch_app(
+    client: &Client,
+    platform: Platform,
+    app_id: &str,
+    arguments: &Map<String, Value>,
+) -> Result<(), CmdError> {
+    match platform {
+        Platform::Ios => {
+            let mut args = app_args(platform, app_id);
+            args["arguments"] = json!(ios_launch_arguments(arguments));
+            execute(client, "mobile: launchApp", args).await
+        }
+        _ if arguments.is_empty() => {
+            execute(client, "mobile: activateApp", app_args(platform, app_id)).await
+        }
+        // Extras need an intent; the launcher activity of the package receives them
+        _ => {
+            let args = json!({
+                "action": "android.intent.action.MAIN",
+                "categories": ["android.intent.category.LAUNCHER"],
+                "package": app_id,
+                "extras": android_extras(arguments),
+            });
+            execute(client, "mobile: startActivity", args).await
+        }
+    }
+}
+
+pub async fn set_permissions(
+    client: &Client,
+    platform: Platform,
+    app_id: &str,
+    permissions: &BTreeMap<String, PermissionState>,
+) -> Result<(), CmdError> {
+    match platform {
+        Platform::Ios => {
+            let args = json!({
+                "bundleId": app_id,
+                "access": ios_permissions(permissions),
+            });
+            execute(client, "mobile: setPermission", args).await
+        }
+        _ => {
+            for args in android_permission_changes(app_id, permissions) {
+                execute(client, "mobile: changePermissions", args).await?;
+            }
+            Ok(())
+        }
+    }
+}
+
+pub(super) async fn execute(client: &Client, script: &str, args: Value) -> Result<(), CmdError> {
+    client.execute(script, vec![args]).await.map(|_| ())
+}
+
+fn app_args(platform: Platform, app_id: &str) -> Value {
+    match platform {
+        Platform::Ios => json!({ "bundleId": app_id }),
+        _ => json!({ "appId": app_id }),
+    }
+}
+
+/// `-key value` pairs, as read by `UserDefaults` and `ProcessInfo`
+pub fn ios_launch_arguments(arguments: &Map<String, Value>) -> Vec<String> {
+    arguments
+        .iter()
+        .flat_map(|(key, value)| [format!("-{}", key), scalar_text(value)])
+        .collect()
+}
+
+/// `[type, key, value]` intent extras, typed after the YAML values
+pub fn android_extras(arguments: &Map<String, Value>) -> Vec<[String; 3]> {
+    arguments
+        .iter()
+        .map(|(key, value)| {
+            let kind = match value {
+                Value::Bool(_) => "z",
+                Value::Number(number) if number.is_i64() => "l",
+                Value::Number(_) => "f",
+                _ => "s",
+            };
+            [kind.to_string(), key.clone(), scalar_text(value)]
+        })
+        .collect()
+}
+
+/// One `mobile: changePermissions` call per action, `all` applied first
+pub fn android_permission_changes(
+    app_id: &str,
+    permissions: &BTreeMap<String, PermissionState>,
+) -> Vec<Value> {
+    let action = |state: &PermissionState| match state {
+        PermissionState::Allow => "grant",
+        PermissionState::Deny | PermissionState::Unset => "revoke",
+    };
+    let mut changes = Vec::new();
+    if let Some(state) = permissions.get("all") {
+        changes
+            .push(json!({ "permissions": "all", "appPackage": app_id, "action": action(state) }));
+    }
+    for wanted in [PermissionState::Allow, PermissionState::Deny] {
+        let names: Vec<&str> = permissions
+            .iter()
+            .filter(|(name, state)| *name != "all" && action(state) == action(&wanted))
+            .flat_map(|(name, _)| android_permission_names(name))
+            .collect();
+        if !names.is_empty() {
+            changes.push(
+                json!({ "permissions": names, "appPackage": app_id, "action": action(&wanted) }),
+            );
+        }
+    }
+    changes
+}
+
+/// Android permissions behind a Maestro permission name; full names pass through
+fn android_permission_names(name: &str) -> Vec<&str> {
+    match name {
+        "camera" => vec!["android.permission.CAMERA"],
+        "location" => vec![
+            "android.permission.ACCESS_FINE_LOCATION",
+            "android.permission.ACCESS_COARSE_LOCATION",
+        ],
+        "contacts" => vec![
+            "android.permission.READ_CONTACTS",
+            "android.permission.WRITE_CONTACTS",
+        ],
+        "calendar" => vec![
+            "android.permission.READ_CALENDAR",
+            "android.permission.WRITE_CALENDAR",
+        ],
+        "microphone" => vec!["android.permission.RECORD_AUDIO"],
+        "notifications" => vec!["android.permission.POST_NOTIFICATIONS"],
+        "phone" => vec![
+            "android.permission.CALL_PHONE",
+            "android.permission.READ_PHONE_STATE",
+        ],
+        "sms" => vec![
+            "android.permission.SEND_SMS",
+            "android.permission.READ_SMS",
+            "android.permission.RECEIVE_SMS",
+        ],
+        "storage" | "medialibrary" => vec![
+            "android.permission.READ_EXTERNAL_STORAGE",
+            "android.permission.WRITE_EXTERNAL_STORAGE",
+        ],
+        "bluetooth" => vec![
+            "android.permission.BLUETOOTH_CONNECT",
+            "android.permission.BLUETOOTH_SCAN",
+        ],
+        name => vec![name],
+    }
+}
+
+/// `mobile: setPermission` access map, `all` expanded and overridden by specific names
+pub fn ios_permissions(permissions: &BTreeMap<String, PermissionState>) -> Map<String, Value> {
+    let access = |state: &PermissionState| match state {
+        PermissionState::Allow => "yes",
+        PermissionState::Deny => "no",
+        PermissionState::Unset => "unset",
+    };
+    let mut map = Map::new();
+    if let Some(state) = permissions.get("all") {
+        for name in IOS_PERMISSIONS {
+            map.insert(name.to_string(), json!(access(state)));
+        }
+    }
+    for (name, state) in permissions.iter().filter(|(name, _)| *name != "all") {
+        map.insert(name.clone(), json!(access(state)));
+    }
+    map
+}
+
+fn scalar_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
@@ -0,0 +1,108 @@
+//! Keys, device buttons and the soft keyboard
+//!
+//! Android presses everything as a keycode. iOS only has hardware buttons for
+//! Home and the volume; Enter, Tab and Backspace are typed into the focused
+//! element instead, and other keys have no equivalent.
+
+use fantoccini::{error::CmdError, Client};
+use serde_json::json;
+
+use super::app::execute;
+use crate::common::models::{Key, Platform};
+
+/// Android `KEYCODE_DEL`, which erases the character before the cursor
+const ANDROID_DELETE: u32 = 67;
+/// XCUITest `XCUIKeyboardKey.delete`
+const IOS_DELETE: &str = "\u{8}";
+
+/// How a key is pressed on iOS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IosKey {
+    /// `mobile: pressButton` with this button name
+    Button(&'static str),
+    /// Typed into the focused element
+    Text(&'static str),
+    /// The back button of the navigation bar
+    Back,
+    Lock,
+}
+
+/// Android keycode of `key`
+pub fn android_keycode(key: Key) -> u32 {
+    match key {
+        Key::Enter => 66,
+        Key::Backspace => ANDROID_DELETE,
+        Key::Back => 4,
+        Key::Home => 3,
+        Key::Lock | Key::Power => 26,
+        Key::Tab => 61,
+        Key::Escape => 111,
+        Key::VolumeUp => 24,
+        Key::VolumeDown => 25,
+        Key::RemoteDpadUp => 19,
+        Key::RemoteDpadDown => 20,
+        Key::RemoteDpadLeft => 21,
+        Key::RemoteDpadRight => 22,
+        Key::RemoteDpadCenter => 23,
+    }
+}
+
+/// iOS equivalent of `key`, if it has one
+pub fn ios_key(key: Key) -> Option<IosKey> {
+    match key {
+        Key::Enter => Some(IosKey::Text("\n")),
+        Key::Backspace => Some(IosKey::Text(IOS_DELETE)),
+        Key::Tab => Some(IosKey::Text("\t")),
+        Key::Back => Some(IosKey::Back),
+        Key::Home => Some(IosKey::Button("home")),
+        Key::Lock | Key::Power => Some(IosKey::Lock),
+        Key::VolumeUp => Some(IosKey::Button("volumeUp")),
+        Key::VolumeDown => Some(IosKey::Button("volumeDown")),
+        Key::Escape
+        | Key::RemoteDpadUp
+        | Key::RemoteDpadDown
+        | Key::RemoteDpadLeft
+        | Key::RemoteDpadRight
+        | Key::RemoteDpadCenter => None,
+    }
+}
+
+pub async fn press_key(client: &Client, platform: Platform, key: Key) -> Result<(), CmdError> {
+    if platform != Platform::Ios {
+        return press_keycode(client, android_keycode(key)).await;
+    }
+    match ios_key(key) {
+        Some(IosKey::Button(name)) => {
+            execute(client, "mobile: pressButton", json!({ "name": name })).await
+        }
+        Some(IosKey::Text(text)) => client.active_element().await?.send_keys(text).await,
+        Some(IosKey::Back) => client.back().await,
+        Some(IosKey::Lock) => execute(client, "mobile: lock", json!({})).await,
+        None => Err(CmdError::InvalidArgument(
+            "pressKey".to_string(),
+            format!("{} has no iOS equivalent", key.name()),
+        )),
+    }
+}
+
+pub async fn hide_keyboard(client: &Client) -> Result<(), CmdError> {
+    execute(client, "mobile: hideKeyboard", json!({})).await
+}
+
+/// Presses delete `count` times in the focused element, erasing up to `count`
+/// characters before the cursor
+pub async fn erase_text(client: &Client, platform: Platform, count: usize) -> Result<(), CmdError> {
+    if platform == Platform::Ios {
+        let element = client.active_element().await?;
+        return element.send_keys(&IOS_DELETE.repeat(count)).await;
+    }
+    // Setting the text would lose the cursor, so delete key by key
+    for _ in 0..count {
+        press_keycode(client, ANDROID_DELETE).await?;
+    }
+    Ok(())
+}
+
+async fn press_keycode(client: &Client, keycode: u32) -> Result<(), CmdError> {
+    execute(client, "mobile: pressKey", json!({ "keycode": keycode })).await
+}
@@ -0,0 +1,263 @@
+//! Platform driver abstraction
+//!
+//! Every backend (Android, iOS, Flutter) implements [`Driver`] so that the
+//! common step executor can run the same `Step` list on any platform.
+
+mod app;
+mod keys;
+#[cfg(test)]
+mod test;
+mod wait;
+
+pub use app::*;
+pub use keys::*;
+pub use wait::*;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use fantoccini::{
+    actions::{InputSource, PointerAction, TouchActions, MOUSE_BUTTON_LEFT},
+    elements::Element,
+    error::CmdError,
+    Client,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::common::models::{ElementState, Key, PermissionState, Platform};
+
+/// Platform-neutral element query, translated by each driver into its own locator
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+#[allow(non_snake_case)]
+pub enum Selector {
+    Hint {
+        hint: String,
+    },
+    AccessibilityId {
+        accessibilityId: String,
+    },
+    Text {
+        text: String,
+    },
+    Xpath {
+        xpath: String,
+    },
+    ClassName {
+        className: String,
+        instance: Option<u32>,
+    },
+    Id {
+        id: String,
+    },
+    IdWithIndex {
+        id: String,
+        index: u32,
+    },
+    Description {
+        description: String,
+    },
+    Index {
+        index: u32,
+    },
+    Tooltip {
+        tooltip: String,
+    },
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selector::Hint { hint } => write!(f, "hint={}", hint),
+            Selector::AccessibilityId { accessibilityId } => {
+                write!(f, "accessibilityId={}", accessibilityId)
+            }
+            Selector::Text { text } => write!(f, "text={}", text),
+            Selector::Xpath { xpath } => write!(f, "xpath={}", xpath),
+            Selector::ClassName {
+                className,
+                instance: Some(instance),
+            } => write!(f, "className={}, instance={}", className, instance),
+            Selector::ClassName { className, .. } => write!(f, "className={}", className),
+            Selector::Id { id } => write!(f, "id={}", id),
+            Selector::IdWithIndex { id, index } => write!(f, "id={}, index={}", id, index),
+            Selector::Description { description } => write!(f, "description={}", description),
+            Selector::Index { index } => write!(f, "index={}", index),
+            Selector::Tooltip { tooltip } => write!(f, "tooltip={}", tooltip),
+        }
+    }
+}
+
+/// Operations a platform backend must provide to run common steps
+#[async_trait]
+pub trait Driver: Send + Sync {
+    /// Platform this driver automates
+    fn platform(&self) -> Platform;
+
+    /// Operating system the app runs on, which decides how apps are managed
+    fn native_platform(&self) -> Platform {
+        self.platform()
+    }
+
+    /// Underlying WebDriver session
+    fn client(&self) -> &Client;
+
+    /// Look for an element matching `selector` once, without waiting
+    async fn find_once(&self, selector: &Selector) -> Result<Option<Element>, CmdError>;
+
+    /// Wait for an element matching `selector`, failing with `WaitTimeout` after `wait.timeout`
+    async fn find(&self, selector: &Selector, wait: WaitOptions) -> Result<Element, CmdError> {
+        wait_until(wait, || self.find_once(selector))
+            .await?
+            .ok_or(CmdError::WaitTimeout)
+    }
+
+    /// Tap on a previously found element
+    async fn tap(&self, element: &Element) -> Result<(), CmdError> {
+        element.click().await
+    }
+
+    /// Whether a previously found element is displayed
+    async fn is_visible(&self, element: &Element) -> Result<bool, CmdError> {
+        element.is_displayed().await
+    }
+
+    /// Whether a previously found element is in `state`
+    async fn element_state(
+        &self,
+        element: &Element,
+        state: ElementState,
+    ) -> Result<bool, CmdError> {
+        let (attribute, on) = state_attribute(self.native_platform(), state);
+        Ok(element.attr(attribute).await?.as_deref() == Some(on))
+    }
+
+    /// Bounds of a previously found element as `(x, y, width, height)`, when known
+    async fn element_rect(
+        &self,
+        element: &Element,
+    ) -> Result<Option<(f64, f64, f64, f64)>, CmdError> {
+        element.rectangle().await.map(Some)
+    }
+
+    /// Type text into the currently focused element
+    async fn type_text(&self, text: &str) -> Result<(), CmdError>;
+
+    /// Swipe between two absolute screen coordinates
+    async fn swipe(
+        &self,
+        from: (i64, i64),
+        to: (i64, i64),
+        duration: Duration,
+    ) -> Result<(), CmdError> {
+        self.client()
+            .perform_actions(swipe_actions(from, to, duration))
+            .await
+    }
+
+    /// Capture the screen as PNG bytes
+    async fn screenshot(&self) -> Result<Vec<u8>, CmdError> {
+        self.client().screenshot().await
+    }
+
+    /// Screen size in pixels as `(width, height)`
+    async fn window_size(&self) -> Result<(u64, u64), CmdError> {
+        self.client().get_window_size().await
+    }
+
+    /// Current UI hierarchy as reported by the automation backend
+    async fn page_source(&self) -> Result<String, CmdError> {
+        self.client().source().await
+    }
+
+    /// Terminate the app if it is running
+    async fn stop_app(&self, app_id: &str) -> Result<(), CmdError> {
+        app::stop_app(self.client(), self.native_platform(), app_id).await
+    }
+
+    /// Clear the data of the app, as if freshly installed
+    async fn clear_app(&self, app_id: &str) -> Result<(), CmdError> {
+        app::clear_app(self.client(), self.native_platform(), app_id).await
+    }
+
+    /// Bring the app to the foreground, passing it `arguments`
+    async fn launch_app(
+        &self,
+        app_id: &str,
+        arguments: &Map<String, Value>,
+    ) -> Result<(), CmdError> {
+        app::launch_app(self.client(), self.native_platform(), app_id, arguments).await
+    }
+
+    /// Grant, deny or reset permissions of the app
+    async fn set_permissions(
+        &self,
+        app_id: &str,
+        permissions: &BTreeMap<String, PermissionState>,
+    ) -> Result<(), CmdError> {
+        app::set_permissions(self.client(), self.native_platform(), app_id, permissions).await
+    }
+
+    /// Press a key or a device button
+    async fn press_key(&self, key: Key) -> Result<(), CmdError> {
+        keys::press_key(self.client(), self.native_platform(), key).await
+    }
+
+    /// Go back, as with the Android back button
+    async fn back(&self) -> Result<(), CmdError> {
+        self.press_key(Key::Back).await
+    }
+
+    /// Hide the soft keyboard if it is shown
+    async fn hide_keyboard(&self) -> Result<(), CmdError> {
+        keys::hide_keyboard(self.client()).await
+    }
+
+    /// Erase up to `count` characters of the focused element
+    async fn erase_text(&self, count: usize) -> Result<(), CmdError> {
+        keys::erase_text(self.client(), self.native_platform(), count).await
+    }
+}
+
+/// Whether `err` means a previously found element is no longer in the page
+///
+/// fantoccini reports a stale element as `NoSuchElement` when the server answers
+/// 404, as the WebDriver spec says, and as a standard error otherwise.
+pub fn is_stale(err: &CmdError) -> bool {
+    match err {
+        CmdError::NoSuchElement(_) => true,
+        CmdError::Standard(err) => err.error() == "stale element reference",
+        _ => false,
+    }
+}
+
+/// Attribute holding `state` on `platform`, and its value when the state is on
+pub fn state_attribute(platform: Platform, state: ElementState) -> (&'static str, &'static str) {
+    match (platform, state) {
+        // XCUITest reports switches and checkboxes through their value
+        (Platform::Ios, ElementState::Checked) => ("value", "1"),
+        (Platform::Ios, ElementState::Focused) => ("hasFocus", "true"),
+        (_, state) => (state.name(), "true"),
+    }
+}
+
+/// Builds the pointer sequence for a single-finger swipe
+pub fn swipe_actions(from: (i64, i64), to: (i64, i64), duration: Duration) -> TouchActions {
+    TouchActions::new("finger".to_string())
+        .then(PointerAction::MoveTo {
+            duration: Some(Duration::from_millis(0)),
+            x: from.0,
+            y: from.1,
+        })
+        .then(PointerAction::Down {
+            button: MOUSE_BUTTON_LEFT,
+        })
+        .then(PointerAction::MoveTo {
+            duration: Some(duration),
+            x: to.0,
+            y: to.1,
+        })
+}
@@ -0,0 +1,163 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use serde_json::{json, Map, Value};
+
+    use crate::common::driver::{
+        android_extras, android_keycode, android_permission_changes, ios_key, ios_launch_arguments,
+        ios_permissions, state_attribute, wait_until, IosKey, Selector, WaitOptions,
+    };
+    use crate::common::models::{ElementState, Key, PermissionState, Platform};
+    use crate::error::RustPilotError;
+
+    fn arguments() -> Map<String, Value> {
+        json!({ "isE2E": true, "retries": 3, "ratio": 0.5, "env": "staging" })
+            .as_object()
+            .unwrap()
+            .clone()
+    }
+
+    #[test]
+    fn test_launch_arguments() {
+        assert_eq!(
+            android_extras(&arguments()),
+            vec![
+                ["s".to_string(), "env".to_string(), "staging".to_string()],
+                ["z".to_string(), "isE2E".to_string(), "true".to_string()],
+                ["f".to_string(), "ratio".to_string(), "0.5".to_string()],
+                ["l".to_string(), "retries".to_string(), "3".to_string()],
+            ]
+        );
+        assert_eq!(
+            ios_launch_arguments(&arguments()),
+            vec!["-env", "staging", "-isE2E", "true", "-ratio", "0.5", "-retries", "3"]
+        );
+    }
+
+    #[test]
+    fn test_permissions() {
+        let permissions = BTreeMap::from([
+            ("all".to_string(), PermissionState::Deny),
+            ("camera".to_string(), PermissionState::Allow),
+            (
+                "android.permission.READ_SMS".to_string(),
+                PermissionState::Allow,
+            ),
+            ("location".to_string(), PermissionState::Unset),
+        ]);
+
+        let changes = android_permission_changes("com.example", &permissions);
+        assert_eq!(
+            changes,
+            vec![
+                json!({ "permissions": "all", "appPackage": "com.example", "action": "revoke" }),
+                json!({
+                    "permissions": ["android.permission.READ_SMS", "android.permission.CAMERA"],
+                    "appPackage": "com.example",
+                    "action": "grant",
+                }),
+                json!({
+                    "permissions": [
+                        "android.permission.ACCESS_FINE_LOCATION",
+                        "android.permission.ACCESS_COARSE_LOCATION",
+                    ],
+                    "appPackage": "com.example",
+                    "action": "revoke",
+                }),
+            ]
+        );
+
+        let access = ios_permissions(&permissions);
+        assert_eq!(access["camera"], "yes");
+        assert_eq!(access["location"], "unset");
+        assert_eq!(access["photos"], "no");
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_polls_until_a_value_or_the_deadline() {
+        let wait = WaitOptions {
+            timeout: Duration::from_millis(50),
+            interval: Duration::from_millis(5),
+        };
+        let checks = AtomicUsize::new(0);
+        let third = wait_until(wait, || async {
+            let count = checks.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok((count == 3).then_some(count))
+        })
+        .await
+        .unwrap();
+        assert_eq!(third, Some(3));
+
+        let never = wait_until(wait, || async { Ok(None::<()>) }).await.unwrap();
+        assert_eq!(never, None);
+
+        // A zero timeout still checks once
+        let checks = AtomicUsize::new(0);
+        let once = WaitOptions {
+            timeout: Duration::ZERO,
+            ..wait
+        };
+        let result = wait_until(once, || async {
+            checks.fetch_add(1, Ordering::SeqCst);
+            Ok(None::<()>)
+        })
+        .await
+        .unwrap();
+        assert_eq!((result, checks.load(Ordering::SeqCst)), (None, 1));
+    }
+
+    #[test]
+    fn test_keys_per_platform() {
+        assert_eq!(android_keycode(Key::Enter), 66);
+        assert_eq!(android_keycode(Key::Back), 4);
+        assert_eq!(android_keycode(Key::VolumeDown), 25);
+
+        assert_eq!(ios_key(Key::Enter), Some(IosKey::Text("\n")));
+        assert_eq!(ios_key(Key::Home), Some(IosKey::Button("home")));
+        assert_eq!(ios_key(Key::RemoteDpadUp), None);
+    }
+
+    #[test]
+    fn test_state_attributes_per_platform() {
+        assert_eq!(
+            state_attribute(Platform::Android, ElementState::Checked),
+            ("checked", "true")
+        );
+        assert_eq!(
+            state_attribute(Platform::Android, ElementState::Focused),
+            ("focused", "true")
+        );
+        assert_eq!(
+            state_attribute(Platform::Ios, ElementState::Checked),
+            ("value", "1")
+        );
+        assert_eq!(
+            state_attribute(Platform::Ios, ElementState::Focused),
+            ("hasFocus", "true")
+        );
+        assert_eq!(
+            state_attribute(Platform::Ios, ElementState::Enabled),
+            ("enabled", "true")
+        );
+    }
+
+    #[test]
+    fn test_selectors_display_as_key_value_pairs() {
+        let selectors = [
+            Selector::Id {
+                id: "submit".to_string(),
+            },
+            Selector::ClassName {
+                className: "android.widget.Button".to_string(),
+                instance: Some(1),
+            },
+        ];
+        assert_eq!(
+            RustPilotError::element_not_found(&selectors).to_string(),
+            "Element not found: id=submit or className=android.widget.Button, instance=1"
+        );
+    }
+}
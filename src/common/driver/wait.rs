@@ -0,0 +1,63 @@
+//! Polling shared by every wait of a run
+//!
+//! Element lookups, visibility assertions and `extendedWaitUntil` all poll
+//! through [`wait_until`], so they honour the same timeout and interval.
+
+use std::future::Future;
+use std::time::Duration;
+
+use appium_client::find::{AppiumFind, By};
+use fantoccini::{elements::Element, error::CmdError, Client};
+use tokio::time::{sleep, Instant};
+
+/// How long waits last when neither the step nor the run sets it
+pub const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Delay between two checks of a wait
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Timeout and polling interval of a wait
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitOptions {
+    pub timeout: Duration,
+    pub interval: Duration,
+}
+
+impl Default for WaitOptions {
+    fn default() -> Self {
+        WaitOptions {
+            timeout: DEFAULT_WAIT_TIMEOUT,
+            interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+}
+
+/// Runs `check` every `wait.interval` until it returns a value or `wait.timeout` is over
+///
+/// `check` always runs at least once, the last time right at the deadline.
+/// Errors of `check` end the wait.
+pub async fn wait_until<T, F, Fut>(wait: WaitOptions, mut check: F) -> Result<Option<T>, CmdError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<T>, CmdError>>,
+{
+    let deadline = Instant::now() + wait.timeout;
+    loop {
+        if let Some(value) = check().await? {
+            return Ok(Some(value));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+        sleep(wait.interval.min(deadline - now)).await;
+    }
+}
+
+/// Single lookup of `by`, `None` when nothing matches
+pub async fn find_once_by(client: &Client, by: By) -> Result<Option<Element>, CmdError> {
+    match client.find_by(by).await {
+        Ok(element) => Ok(Some(element)),
+        Err(CmdError::NoSuchElement(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
@@ -0,0 +1,659 @@
+//! Platform-independent step executor
+//!
+//! Runs a flattened list of Maestro-style steps against any [`Driver`].
+
+#[cfg(test)]
+mod test;
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use fantoccini::{elements::Element, error::CmdError};
+
+use crate::common::context::{ExecutionContext, PlainLogger};
+use crate::common::driver::{
+    is_stale, wait_until, Driver, Selector, WaitOptions, DEFAULT_POLL_INTERVAL,
+    DEFAULT_WAIT_TIMEOUT,
+};
+use crate::common::models::{
+    ElementOptions, ElementSelector, ElementState, ScrollDirection, ScrollUntilVisible,
+    SourceLocation, Step, SwipeOptions, WaitCondition, DEFAULT_ERASE_COUNT,
+};
+use crate::common::steps::FlowStep;
+use crate::common::tags::{error_tag, info_tag, ok_tag, warning_tag};
+use crate::common::utils::{error_take_screenshot, save_screenshot, screenshot_path};
+use crate::error::{Result, RustPilotError};
+use crate::reporting::{Artifact, RunResult, RunStatus, StepResult, SCREENSHOT_DIR};
+use crate::secrets;
+
+/// Knobs for how steps are executed, set from the command line
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionOptions {
+    /// How long element waits last; [`DEFAULT_WAIT_TIMEOUT`] when `None`
+    pub timeout: Option<Duration>,
+    /// Delay between two checks of a wait; [`DEFAULT_POLL_INTERVAL`] when `None`
+    pub poll_interval: Option<Duration>,
+    /// How many times a failing lookup or assertion step is retried before the run fails
+    pub retries: u32,
+    /// Directory of the reports, which screenshots are saved under; the current directory when `None`
+    pub report_dir: Option<PathBuf>,
+    /// Whether every passing step gets a screenshot too, not only the failing one
+    pub capture_steps: bool,
+}
+
+impl ExecutionOptions {
+    /// Wait of a step, whose own `timeout` in milliseconds wins over the run's
+    pub fn wait(&self, step_timeout: Option<u64>) -> WaitOptions {
+        WaitOptions {
+            timeout: step_timeout
+                .map(Duration::from_millis)
+                .or(self.timeout)
+                .unwrap_or(DEFAULT_WAIT_TIMEOUT),
+            interval: self.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL),
+        }
+    }
+}
+
+/// Executes `steps` in order on `driver`, returning one result per step
+///
+/// The first failing step gets an error screenshot, and with
+/// [`ExecutionOptions::capture_steps`] so does every passing step; every step
+/// after the failing one is reported as skipped instead of being run.
+///
+/// Each step runs in `context` narrowed to the flow it was declared in.
+pub async fn execute_steps<D: Driver + ?Sized>(
+    driver: &D,
+    steps: Vec<FlowStep>,
+    options: &ExecutionOptions,
+    context: &ExecutionContext,
+) -> RunResult {
+    let mut run = RunResult::new(driver.platform().name().to_string());
+    let run_started = Instant::now();
+    let mut results = Vec::with_capacity(steps.len());
+    let mut failed = false;
+    for (index, flow_step) in steps.into_iter().enumerate() {
+        let context = context.for_step(&flow_step);
+        let source = flow_step.source;
+        let step = match context.variables.interpolate_step(&flow_step.step) {
+            Ok(step) => step,
+            Err(err) if !failed => {
+                context.error(&err);
+                log_failure_source(&context, source.as_ref());
+                failed = true;
+                let step = &flow_step.step;
+                let mut result =
+                    StepResult::failed(step.description(), err.to_string(), Duration::ZERO, None)
+                        .for_step(step, step_selector(step));
+                result.source = source;
+                results.push(result);
+                continue;
+            }
+            Err(_) => flow_step.step,
+        };
+        if let Step::InputText { inputText } = &step {
+            if inputText.is_secret() {
+                secrets::register_value(inputText.text());
+            }
+        }
+        let description = step.description();
+        let selector = step_selector(&step);
+        if failed {
+            let mut result = StepResult::skipped(description, "A previous step failed".to_string())
+                .for_step(&step, selector);
+            result.source = source;
+            results.push(result);
+            continue;
+        }
+        let started_at = Local::now();
+        let started = Instant::now();
+        let mut attempt = 0;
+        let outcome = loop {
+            match execute_step(driver, step.clone(), options, &context).await {
+                Err(err) if attempt < options.retries && step.is_retryable() => {
+                    attempt += 1;
+                    context.warning(format!(
+                        "{} failed ({}), retrying {}/{}",
+                        description, err, attempt, options.retries
+                    ));
+                }
+                outcome => break outcome,
+            }
+        };
+        let report_dir = options.report_dir.as_deref().unwrap_or(Path::new("."));
+        let flow = source
+            .as_ref()
+            .and_then(|source| source.include_chain.first())
+            .or(context.flow.as_ref());
+        let path = screenshot_path(Path::new(SCREENSHOT_DIR), flow.map(String::as_str), index);
+        let result = match outcome {
+            Ok(None) => {
+                let mut result = StepResult::passed(description, started.elapsed());
+                if options.capture_steps {
+                    let screenshot = save_screenshot(driver, &context, report_dir, &path).await;
+                    result
+                        .artifacts
+                        .extend(screenshot.map(Artifact::screenshot));
+                }
+                result
+            }
+            Ok(Some(reason)) => StepResult::skipped(description, reason),
+            Err(err) => {
+                let duration = started.elapsed();
+                log_failure_source(&context, source.as_ref());
+                let screenshot = error_take_screenshot(driver, &context, report_dir, &path).await;
+                failed = true;
+                StepResult::failed(description, err.to_string(), duration, screenshot)
+            }
+        };
+        let mut result = result.for_step(&step, selector);
+        result.started_at = Some(started_at);
+        result.source = source;
+        results.push(result);
+    }
+    run.steps = results;
+    run.duration = run_started.elapsed();
+    if failed {
+        run.status = RunStatus::Failed;
+    }
+    run
+}
+
+/// Points at the flow file line of the step that just failed
+fn log_failure_source(context: &ExecutionContext, source: Option<&SourceLocation>) {
+    match (source, &context.flow) {
+        (Some(source), _) => context.error(format!("Failed step declared at {}", source)),
+        (None, Some(flow)) => context.error(format!("Failed step declared in {}", flow)),
+        (None, None) => {}
+    }
+}
+
+/// Executes a single step, returning the reason when it was skipped
+async fn execute_step<D: Driver + ?Sized>(
+    driver: &D,
+    step: Step,
+    options: &ExecutionOptions,
+    context: &ExecutionContext,
+) -> Result<Option<String>> {
+    match step {
+        Step::Swipe { swipe } => {
+            let (x_y_from, x_y_end) = swipe_coordinates(driver, &swipe).await?;
+            let swipe_options = swipe;
+            let sp = context.start_spinner(format!("Swiping: {:?}", swipe_options));
+
+            if let Err(err) = driver
+                .swipe(x_y_from, x_y_end, Duration::from_millis(500))
+                .await
+            {
+                sp.stop_with_symbol(&format!("{} Error swiping: {:?}", error_tag(), err));
+                return Err(err.into());
+            }
+
+            sp.stop_with_symbol(&format!("{} Swiped {:?}", ok_tag(), swipe_options));
+        }
+        Step::AssertVisible { assertVisible } => {
+            let sp = context.start_spinner(format!("Asserting visible: {}", assertVisible.label()));
+            let wait = options.wait(assertVisible.timeout());
+            if wait_for_visible(driver, &assertVisible, wait)
+                .await?
+                .is_none()
+            {
+                return missing_element(&sp, &assertVisible);
+            }
+            sp.stop_with_symbol(&format!(
+                "{} Element {} visible",
+                ok_tag(),
+                assertVisible.label()
+            ));
+        }
+        Step::AssertNotVisible { assertNotVisible } => {
+            let sp = context.start_spinner(format!(
+                "Asserting not visible: {}",
+                assertNotVisible.label()
+            ));
+            let wait = options.wait(assertNotVisible.timeout());
+            if !wait_for_not_visible(driver, &assertNotVisible, wait).await? {
+                sp.stop_with_symbol(&format!(
+                    "{} Element {} visible",
+                    error_tag(),
+                    assertNotVisible.label()
+                ));
+                return Err(RustPilotError::Assertion(format!(
+                    "Element {} is visible",
+                    assertNotVisible.label()
+                )));
+            }
+            sp.stop_with_symbol(&format!(
+                "{} Element {} not visible",
+                ok_tag(),
+                assertNotVisible.label()
+            ));
+        }
+        Step::TapOn { tapOn } => {
+            let sp = context.start_spinner(format!("Tapping on: {}", tapOn.label()));
+            let wait = options.wait(tapOn.timeout());
+            let Some((element, selector)) = wait_for_visible(driver, &tapOn, wait).await? else {
+                return missing_element(&sp, &tapOn);
+            };
+            driver.tap(&element).await?;
+            sp.stop_with_symbol(&format!("{} Tapped on: {}", ok_tag(), selector));
+        }
+        Step::ExtendedWaitUntil { extendedWaitUntil } => {
+            let wait = options.wait(
+                extendedWaitUntil
+                    .timeout
+                    .or(extendedWaitUntil.condition.element().timeout()),
+            );
+            match extendedWaitUntil.condition {
+                WaitCondition::Visible(element) => {
+                    let sp = context
+                        .start_spinner(format!("Waiting until visible: {}", element.label()));
+                    if wait_for_visible(driver, &element, wait).await?.is_none() {
+                        sp.stop_with_symbol(&format!(
+                            "{} {} not visible",
+                            error_tag(),
+                            element.label()
+                        ));
+                        return Err(RustPilotError::Timeout(format!(
+                            "{} not visible after {} ms",
+                            element.label(),
+                            wait.timeout.as_millis()
+                        )));
+                    }
+                    sp.stop_with_symbol(&format!("{} {} visible", ok_tag(), element.label()));
+                }
+                WaitCondition::NotVisible(element) => {
+                    let sp = context
+                        .start_spinner(format!("Waiting until not visible: {}", element.label()));
+                    if !wait_for_not_visible(driver, &element, wait).await? {
+                        sp.stop_with_symbol(&format!(
+                            "{} {} still visible",
+                            error_tag(),
+                            element.label()
+                        ));
+                        return Err(RustPilotError::Timeout(format!(
+                            "{} still visible after {} ms",
+                            element.label(),
+                            wait.timeout.as_millis()
+                        )));
+                    }
+                    sp.stop_with_symbol(&format!("{} {} not visible", ok_tag(), element.label()));
+                }
+            }
+        }
+        Step::InputText { inputText } => {
+            let sp = context.start_spinner(format!("Inserting {} ", inputText.text()));
+            driver.type_text(inputText.text()).await?;
+            sp.stop_with_symbol(&format!("{} Inserted {}", ok_tag(), inputText.text()));
+        }
+        Step::RunScript { runScript } => {
+            let sp = context.start_spinner(format!("Running script: {}", runScript));
+            sp.stop_with_symbol(&format!("{} Ran script: {}", ok_tag(), runScript));
+        }
+        Step::ScrollUntilVisible { scrollUntilVisible } => {
+            let label = scrollUntilVisible.element.label();
+            let sp = context.start_spinner(format!(
+                "Scrolling {} until visible: {}",
+                scrollUntilVisible.direction.name(),
+                label
+            ));
+            match scroll_until_visible(driver, &scrollUntilVisible).await {
+                Ok(swipes) => sp.stop_with_symbol(&format!(
+                    "{} Element {} visible after {} swipes",
+                    ok_tag(),
+                    label,
+                    swipes
+                )),
+                Err(err) => {
+                    sp.stop_with_symbol(&format!("{} {}", error_tag(), err));
+                    return Err(err);
+                }
+            }
+        }
+        Step::PressKey { pressKey } => {
+            let sp = context.start_spinner(format!("Pressing key: {}", pressKey.name()));
+            driver.press_key(pressKey).await?;
+            sp.stop_with_symbol(&format!("{} Pressed key: {}", ok_tag(), pressKey.name()));
+        }
+        Step::Back { .. } => {
+            let sp = context.start_spinner("Going back".to_string());
+            driver.back().await?;
+            sp.stop_with_symbol(&format!("{} Went back", ok_tag()));
+        }
+        Step::HideKeyboard { .. } => {
+            let sp = context.start_spinner("Hiding keyboard".to_string());
+            driver.hide_keyboard().await?;
+            sp.stop_with_symbol(&format!("{} Keyboard hidden", ok_tag()));
+        }
+        Step::EraseText { eraseText } => {
+            let count = eraseText.unwrap_or(DEFAULT_ERASE_COUNT);
+            let sp = context.start_spinner(format!("Erasing {} characters", count));
+            driver.erase_text(count).await?;
+            sp.stop_with_symbol(&format!("{} Erased up to {} characters", ok_tag(), count));
+        }
+        Step::LaunchApp { launchApp } => {
+            let app_id = target_app_id(launchApp.appId, context, "launchApp")?;
+            let sp = context.start_spinner(format!("Launching app: {}", app_id));
+            if launchApp.stopApp.unwrap_or(true) {
+                driver.stop_app(&app_id).await?;
+            }
+            if launchApp.clearState {
+                context.info(format!("{} Clearing state of {}", info_tag(), app_id));
+                driver.clear_app(&app_id).await?;
+            }
+            if let Some(permissions) = &launchApp.permissions {
+                driver.set_permissions(&app_id, permissions).await?;
+            }
+            driver
+                .launch_app(&app_id, &launchApp.arguments.unwrap_or_default())
+                .await?;
+            sp.stop_with_symbol(&format!("{} Launched app: {}", ok_tag(), app_id));
+        }
+        Step::StopApp { stopApp } => {
+            let app_id = target_app_id(stopApp, context, "stopApp")?;
+            let sp = context.start_spinner(format!("Stopping app: {}", app_id));
+            driver.stop_app(&app_id).await?;
+            sp.stop_with_symbol(&format!("{} Stopped app: {}", ok_tag(), app_id));
+        }
+        other => {
+            context.info(format!("{} Step {:?} not developed", info_tag(), other));
+            return Ok(Some("Step not developed".to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Swipes until the element of `scroll` is shown as it asks, returning how many swipes it took
+///
+/// Fails once the timeout is over, or as soon as a swipe leaves the page source
+/// unchanged: the end of the content was reached.
+async fn scroll_until_visible<D: Driver + ?Sized>(
+    driver: &D,
+    scroll: &ScrollUntilVisible,
+) -> Result<usize> {
+    let selectors = element_selectors(&scroll.element)?;
+    let states = scroll.element.states();
+    let window = driver.window_size().await?;
+    let (from, to, duration) = scroll_gesture(scroll.direction, scroll.speed, window);
+    let deadline = Instant::now() + Duration::from_millis(scroll.timeout);
+    let mut last_source = None;
+    let mut swipes = 0;
+    loop {
+        if let Some((element, _)) = find_shown(driver, &selectors, &states).await? {
+            let shown = match driver.element_rect(&element).await? {
+                Some(rect) => {
+                    visible_percentage(rect, window) >= scroll.visibilityPercentage as f64
+                        && (!scroll.centerElement || reached_center(rect, window, scroll.direction))
+                }
+                None => true,
+            };
+            if shown {
+                return Ok(swipes);
+            }
+        }
+        if Instant::now() >= deadline {
+            return Err(RustPilotError::Timeout(format!(
+                "{} not visible after scrolling {} for {} ms",
+                scroll.element.label(),
+                scroll.direction.name(),
+                scroll.timeout
+            )));
+        }
+        let source = driver.page_source().await?;
+        if last_source.as_ref() == Some(&source) {
+            return Err(RustPilotError::Assertion(format!(
+                "Reached the end of the content without finding {}",
+                scroll.element.label()
+            )));
+        }
+        last_source = Some(source);
+        driver.swipe(from, to, duration).await?;
+        swipes += 1;
+    }
+}
+
+/// Swipe along the middle of the screen moving the content in `direction`
+///
+/// Covers 40% of the screen; `speed` 0 takes 1.1 s and 100 takes 0.1 s.
+pub fn scroll_gesture(
+    direction: ScrollDirection,
+    speed: u32,
+    window: (u64, u64),
+) -> ((i64, i64), (i64, i64), Duration) {
+    let (width, height) = (window.0 as f64, window.1 as f64);
+    let point = |x: f64, y: f64| ((width * x).round() as i64, (height * y).round() as i64);
+    let (from, to) = match direction {
+        ScrollDirection::Down => (point(0.5, 0.7), point(0.5, 0.3)),
+        ScrollDirection::Up => (point(0.5, 0.3), point(0.5, 0.7)),
+        ScrollDirection::Right => (point(0.7, 0.5), point(0.3, 0.5)),
+        ScrollDirection::Left => (point(0.3, 0.5), point(0.7, 0.5)),
+    };
+    let duration = Duration::from_millis(100 + 10 * (100 - speed.min(100)) as u64);
+    (from, to, duration)
+}
+
+/// Share of an `(x, y, width, height)` rectangle inside the window, in percent
+pub fn visible_percentage(rect: (f64, f64, f64, f64), window: (u64, u64)) -> f64 {
+    let (x, y, width, height) = rect;
+    if width <= 0.0 || height <= 0.0 {
+        return 0.0;
+    }
+    let visible_width = ((x + width).min(window.0 as f64) - x.max(0.0)).max(0.0);
+    let visible_height = ((y + height).min(window.1 as f64) - y.max(0.0)).max(0.0);
+    visible_width * visible_height * 100.0 / (width * height)
+}
+
+/// Whether scrolling in `direction` brought the center of `rect` to the middle of the screen
+///
+/// An element already past the middle counts, so scrolling never moves it further away.
+pub fn reached_center(
+    rect: (f64, f64, f64, f64),
+    window: (u64, u64),
+    direction: ScrollDirection,
+) -> bool {
+    let (x, y, width, height) = rect;
+    let center = (x + width / 2.0, y + height / 2.0);
+    let (window_width, window_height) = (window.0 as f64, window.1 as f64);
+    match direction {
+        ScrollDirection::Down => center.1 <= window_height * 0.6,
+        ScrollDirection::Up => center.1 >= window_height * 0.4,
+        ScrollDirection::Right => center.0 <= window_width * 0.6,
+        ScrollDirection::Left => center.0 >= window_width * 0.4,
+    }
+}
+
+/// Reports a step whose element never showed up, skipping the step when the element is optional
+fn missing_element(sp: &PlainLogger, element: &ElementSelector) -> Result<Option<String>> {
+    if element.is_optional() {
+        sp.stop_with_symbol(&format!(
+            "{} Optional element {} not found, skipping",
+            warning_tag(),
+            element.label()
+        ));
+        return Ok(Some(format!(
+            "Optional element {} not found",
+            element.label()
+        )));
+    }
+    sp.stop_with_symbol(&format!(
+        "{} Can't find element: {}",
+        error_tag(),
+        element.label()
+    ));
+    Err(RustPilotError::element_not_found(&element_selectors(
+        element,
+    )?))
+}
+
+/// Waits until `element` is displayed and in its states, returning it with the selector that matched
+async fn wait_for_visible<D: Driver + ?Sized>(
+    driver: &D,
+    element: &ElementSelector,
+    wait: WaitOptions,
+) -> Result<Option<(Element, Selector)>> {
+    let selectors = &element_selectors(element)?;
+    let states = &element.states();
+    Ok(wait_until(wait, || find_shown(driver, selectors, states)).await?)
+}
+
+/// Waits until `element` is hidden, gone or out of its states, returning whether it happened
+async fn wait_for_not_visible<D: Driver + ?Sized>(
+    driver: &D,
+    element: &ElementSelector,
+    wait: WaitOptions,
+) -> Result<bool> {
+    let selectors = &element_selectors(element)?;
+    let states = &element.states();
+    let gone = wait_until(wait, || async move {
+        Ok(find_shown(driver, selectors, states)
+            .await?
+            .is_none()
+            .then_some(()))
+    })
+    .await?;
+    Ok(gone.is_some())
+}
+
+/// Looks once for an element of `selectors` that is displayed and in every state of `states`
+///
+/// Only the first element each selector matches is considered.
+async fn find_shown<D: Driver + ?Sized>(
+    driver: &D,
+    selectors: &[Selector],
+    states: &[(ElementState, bool)],
+) -> std::result::Result<Option<(Element, Selector)>, CmdError> {
+    'selectors: for selector in selectors {
+        let Some(element) = driver.find_once(selector).await? else {
+            continue;
+        };
+        // An element can go stale between the lookup and the checks; it then counts as not shown
+        if unless_stale(driver.is_visible(&element).await)? != Some(true) {
+            continue;
+        }
+        for &(state, expected) in states {
+            if unless_stale(driver.element_state(&element, state).await)? != Some(expected) {
+                continue 'selectors;
+            }
+        }
+        return Ok(Some((element, selector.clone())));
+    }
+    Ok(None)
+}
+
+/// The outcome of a check on a found element, `None` when the element went stale meanwhile
+fn unless_stale<T>(
+    outcome: std::result::Result<T, CmdError>,
+) -> std::result::Result<Option<T>, CmdError> {
+    match outcome {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if is_stale(&err) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// App a step acts on: its own `appId`, else the one of its flow
+fn target_app_id(
+    app_id: Option<String>,
+    context: &ExecutionContext,
+    command: &str,
+) -> Result<String> {
+    app_id.or_else(|| context.app_id.clone()).ok_or_else(|| {
+        RustPilotError::Config(format!(
+            "{} needs an appId, in the step or in the flow header",
+            command
+        ))
+    })
+}
+
+/// Converts the percentage-based swipe options into absolute screen coordinates
+async fn swipe_coordinates<D: Driver + ?Sized>(
+    driver: &D,
+    swipe: &SwipeOptions,
+) -> Result<((i64, i64), (i64, i64))> {
+    let device_size = driver.window_size().await?;
+    let start = swipe.start.to_f64()?;
+    let end = swipe.end.to_f64()?;
+    let x_y_from: (i64, i64) = (
+        (device_size.0 as f64 * (start.0) / 100.0).round() as i64,
+        (device_size.1 as f64 * (start.1) / 100.0).round() as i64,
+    );
+    let x_y_end: (i64, i64) = (
+        (device_size.0 as f64 * (end.0) / 100.0).round() as i64,
+        (device_size.1 as f64 * (end.1) / 100.0).round() as i64,
+    );
+    Ok((x_y_from, x_y_end))
+}
+
+/// Element targeted by `step`, as recorded in the step results
+pub fn step_selector(step: &Step) -> Option<Selector> {
+    match step {
+        Step::TapOn { tapOn: element }
+        | Step::AssertVisible {
+            assertVisible: element,
+        }
+        | Step::AssertNotVisible {
+            assertNotVisible: element,
+        }
+        | Step::ScrollUntilVisible {
+            scrollUntilVisible: ScrollUntilVisible { element, .. },
+        } => element_selector(element).ok(),
+        Step::ExtendedWaitUntil { extendedWaitUntil } => {
+            element_selector(extendedWaitUntil.condition.element()).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Selector of an element, recorded in reports; a bare string reads as a text
+pub fn element_selector(element: &ElementSelector) -> Result<Selector> {
+    match element {
+        ElementSelector::Text(text) => Ok(Selector::Text { text: text.clone() }),
+        ElementSelector::Options(options) => options_selector(options.clone()),
+    }
+}
+
+/// Selectors an element may match; a bare string matches a text or a description
+pub fn element_selectors(element: &ElementSelector) -> Result<Vec<Selector>> {
+    match element {
+        ElementSelector::Text(text) => Ok(vec![
+            Selector::Text { text: text.clone() },
+            Selector::Description {
+                description: text.clone(),
+            },
+        ]),
+        ElementSelector::Options(options) => Ok(vec![options_selector(options.clone())?]),
+    }
+}
+
+/// Picks the selector described by a block of element options
+pub fn options_selector(tap_on_options: ElementOptions) -> Result<Selector> {
+    let selector = if let Some(text) = tap_on_options.text {
+        Selector::Text { text }
+    } else if let Some(id) = tap_on_options.id {
+        if let Some(index) = tap_on_options.index {
+            Selector::IdWithIndex { id, index }
+        } else {
+            Selector::Id { id }
+        }
+    } else if let Some(index) = tap_on_options.index {
+        Selector::Index { index }
+    } else if let Some(description) = tap_on_options.description {
+        Selector::Description { description }
+    } else if let Some(class_name) = tap_on_options.className {
+        Selector::ClassName {
+            className: class_name,
+            instance: tap_on_options.instance,
+        }
+    } else if let Some(hint) = tap_on_options.hint {
+        Selector::Hint { hint }
+    } else if let Some(tooltip) = tap_on_options.tooltip {
+        Selector::Tooltip { tooltip }
+    } else {
+        return Err(RustPilotError::parse(
+            "element selector",
+            "no selector given (expected text, id, index, description, className, hint or tooltip)",
+        ));
+    };
+    Ok(selector)
+}
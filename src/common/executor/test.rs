@@ -0,0 +1,579 @@
+#[cfg(test)]
+mod tests {
+    use crate::common::driver::Selector;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use fantoccini::elements::{Element, ElementRef};
+    use fantoccini::{error::CmdError, Client, ClientBuilder};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    use crate::common::context::{ExecutionContext, LogLevel, OutputSink};
+    use crate::common::driver::{Driver, DEFAULT_POLL_INTERVAL, DEFAULT_WAIT_TIMEOUT};
+    use crate::common::executor::{
+        element_selectors, execute_steps, options_selector, reached_center, scroll_gesture,
+        step_selector, visible_percentage, ExecutionOptions,
+    };
+    use crate::common::models::{
+        ElementOptions, ElementSelector, ElementState, InputText, Key, Platform, ScrollDirection,
+        Step,
+    };
+    use crate::common::steps::FlowStep;
+    use crate::common::utils::deserialize_test_file;
+    use crate::common::variables::Variables;
+    use crate::error::RustPilotError;
+    use crate::reporting::{RunResult, RunStatus, StepStatus};
+
+    fn empty_option() -> ElementOptions {
+        ElementOptions::default()
+    }
+
+    /// Driver without a device: records every call and fails the one named `fail_on`;
+    /// elements are only found on a `session`, and screenshots fail unless `screen` is set
+    #[derive(Default)]
+    struct FakeDriver {
+        calls: Mutex<Vec<String>>,
+        fail_on: Option<&'static str>,
+        screen: Option<&'static [u8]>,
+        session: Option<Client>,
+    }
+
+    impl FakeDriver {
+        fn failing_on(call: &'static str) -> Self {
+            FakeDriver {
+                fail_on: Some(call),
+                ..FakeDriver::default()
+            }
+        }
+
+        fn call(&self, call: &str) -> Result<(), CmdError> {
+            self.calls.lock().unwrap().push(call.to_string());
+            match self.fail_on {
+                Some(failing) if failing == call => Err(CmdError::InvalidArgument(
+                    call.to_string(),
+                    "fake failure".to_string(),
+                )),
+                _ => Ok(()),
+            }
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl Driver for FakeDriver {
+        fn platform(&self) -> Platform {
+            Platform::Android
+        }
+
+        fn client(&self) -> &Client {
+            unreachable!("the fake driver overrides every call that needs a session")
+        }
+
+        async fn find_once(&self, _selector: &Selector) -> Result<Option<Element>, CmdError> {
+            self.call("find")?;
+            Ok(self.session.clone().map(|client| {
+                Element::from_element_id(client, ElementRef::from("element".to_string()))
+            }))
+        }
+
+        async fn is_visible(&self, _element: &Element) -> Result<bool, CmdError> {
+            self.call("visible")?;
+            Ok(true)
+        }
+
+        async fn element_state(
+            &self,
+            element: &Element,
+            state: ElementState,
+        ) -> Result<bool, CmdError> {
+            self.call("state")?;
+            Ok(element.attr(state.name()).await?.as_deref() == Some("true"))
+        }
+
+        async fn type_text(&self, _text: &str) -> Result<(), CmdError> {
+            self.call("type")
+        }
+
+        async fn swipe(
+            &self,
+            _from: (i64, i64),
+            _to: (i64, i64),
+            _duration: Duration,
+        ) -> Result<(), CmdError> {
+            self.call("swipe")
+        }
+
+        async fn screenshot(&self) -> Result<Vec<u8>, CmdError> {
+            self.call("screenshot")?;
+            self.screen
+                .map(<[u8]>::to_vec)
+                .ok_or_else(|| CmdError::NotJson("no screen".to_string()))
+        }
+
+        async fn window_size(&self) -> Result<(u64, u64), CmdError> {
+            Ok((1000, 2000))
+        }
+
+        async fn page_source(&self) -> Result<String, CmdError> {
+            self.call("source")?;
+            Ok("<hierarchy/>".to_string())
+        }
+
+        async fn press_key(&self, key: Key) -> Result<(), CmdError> {
+            self.call(&format!("pressKey {}", key.name()))
+        }
+
+        async fn back(&self) -> Result<(), CmdError> {
+            self.call("back")
+        }
+
+        async fn hide_keyboard(&self) -> Result<(), CmdError> {
+            self.call("hideKeyboard")
+        }
+
+        async fn erase_text(&self, _count: usize) -> Result<(), CmdError> {
+            self.call("eraseText")
+        }
+    }
+
+    /// Session on a local WebDriver endpoint that accepts every command, except that
+    /// each element it is asked about has gone stale
+    async fn fake_session() -> Client {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve_webdriver(stream));
+            }
+        });
+        ClientBuilder::native().connect(&url).await.unwrap()
+    }
+
+    async fn serve_webdriver(stream: TcpStream) {
+        let mut stream = BufReader::new(stream);
+        let mut request = String::new();
+        while stream.read_line(&mut request).await.unwrap_or(0) > 0 {
+            let mut length = 0;
+            let mut header = String::new();
+            while stream.read_line(&mut header).await.unwrap_or(0) > 0 && header.trim() != "" {
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+                header.clear();
+            }
+            let mut body = vec![0; length];
+            if stream.read_exact(&mut body).await.is_err() {
+                return;
+            }
+            let (status, body) = if request.contains("/element/") {
+                (
+                    "404 Not Found",
+                    r#"{"value":{"error":"stale element reference","message":"gone","stacktrace":""}}"#,
+                )
+            } else if request.starts_with("POST /session ") {
+                (
+                    "200 OK",
+                    r#"{"value":{"sessionId":"fake","capabilities":{}}}"#,
+                )
+            } else {
+                ("200 OK", r#"{"value":null}"#)
+            };
+            let response = format!(
+                "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            );
+            if stream
+                .get_mut()
+                .write_all(response.as_bytes())
+                .await
+                .is_err()
+            {
+                return;
+            }
+            request.clear();
+        }
+    }
+
+    struct QuietSink;
+
+    impl OutputSink for QuietSink {
+        fn log(&self, _level: LogLevel, _message: &str, _indent_level: usize) {}
+    }
+
+    async fn run_flow(driver: &FakeDriver, flow: &str, options: &ExecutionOptions) -> RunResult {
+        let (_, steps) = deserialize_test_file(&format!("appId: app\n---\n{}", flow)).unwrap();
+        let steps = steps
+            .into_iter()
+            .map(|step| FlowStep {
+                step,
+                variables: Arc::new(Variables::default()),
+                source: None,
+                app_id: None,
+            })
+            .collect();
+        let context = ExecutionContext::new(Arc::new(QuietSink));
+        execute_steps(driver, steps, options, &context).await
+    }
+
+    fn statuses(run: &RunResult) -> Vec<StepStatus> {
+        run.steps.iter().map(|step| step.status).collect()
+    }
+
+    #[test]
+    fn test_tap_on_option_text_takes_precedence() {
+        let options = ElementOptions {
+            text: Some("Login".to_string()),
+            id: Some("login_button".to_string()),
+            ..empty_option()
+        };
+
+        let selector = options_selector(options).unwrap();
+        assert!(matches!(selector, Selector::Text { text } if text == "Login"));
+    }
+
+    #[test]
+    fn test_tap_on_option_id_with_index() {
+        let options = ElementOptions {
+            id: Some("item".to_string()),
+            index: Some(2),
+            ..empty_option()
+        };
+
+        let selector = options_selector(options).unwrap();
+        assert!(
+            matches!(selector, Selector::IdWithIndex { id, index } if id == "item" && index == 2)
+        );
+    }
+
+    #[test]
+    fn test_tap_on_option_class_name_keeps_instance() {
+        let options = ElementOptions {
+            className: Some("android.widget.Button".to_string()),
+            instance: Some(1),
+            ..empty_option()
+        };
+
+        let selector = options_selector(options).unwrap();
+        assert!(matches!(
+            selector,
+            Selector::ClassName {
+                instance: Some(1),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_tap_on_option_hint() {
+        let options = ElementOptions {
+            hint: Some("Email".to_string()),
+            ..empty_option()
+        };
+
+        let selector = options_selector(options).unwrap();
+        assert!(matches!(selector, Selector::Hint { hint } if hint == "Email"));
+    }
+
+    #[test]
+    fn test_tap_on_option_without_selector_is_parse_error() {
+        let result = options_selector(empty_option());
+        assert!(matches!(result, Err(RustPilotError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_step_selector_for_reports() {
+        let tap = Step::TapOn {
+            tapOn: ElementSelector::Options(ElementOptions {
+                id: Some("submit".to_string()),
+                index: Some(1),
+                ..empty_option()
+            }),
+        };
+        assert!(matches!(
+            step_selector(&tap),
+            Some(Selector::IdWithIndex { id, index: 1 }) if id == "submit"
+        ));
+
+        let assert_visible = Step::AssertVisible {
+            assertVisible: ElementSelector::Text("Welcome".to_string()),
+        };
+        assert!(matches!(
+            step_selector(&assert_visible),
+            Some(Selector::Text { text }) if text == "Welcome"
+        ));
+
+        let input = Step::InputText {
+            inputText: InputText::InputTextString("hello".to_string()),
+        };
+        assert!(step_selector(&input).is_none());
+        assert_eq!(input.kind(), "inputText");
+    }
+
+    #[test]
+    fn test_scroll_gesture_moves_content_in_direction() {
+        let (from, to, duration) = scroll_gesture(ScrollDirection::Down, 40, (1000, 2000));
+        assert_eq!((from, to), ((500, 1400), (500, 600)));
+        assert_eq!(duration, Duration::from_millis(700));
+
+        let (from, to, duration) = scroll_gesture(ScrollDirection::Left, 100, (1000, 2000));
+        assert_eq!((from, to), ((300, 1000), (700, 1000)));
+        assert_eq!(duration, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_visible_percentage_and_center() {
+        let window = (1000, 2000);
+        assert_eq!(
+            visible_percentage((0.0, 100.0, 500.0, 200.0), window),
+            100.0
+        );
+        assert_eq!(
+            visible_percentage((0.0, 1900.0, 500.0, 200.0), window),
+            50.0
+        );
+        assert_eq!(visible_percentage((0.0, 2100.0, 500.0, 200.0), window), 0.0);
+
+        assert!(!reached_center(
+            (0.0, 1800.0, 500.0, 100.0),
+            window,
+            ScrollDirection::Down
+        ));
+        assert!(reached_center(
+            (0.0, 900.0, 500.0, 100.0),
+            window,
+            ScrollDirection::Down
+        ));
+        assert!(reached_center(
+            (0.0, 100.0, 500.0, 100.0),
+            window,
+            ScrollDirection::Down
+        ));
+        assert!(!reached_center(
+            (0.0, 100.0, 500.0, 100.0),
+            window,
+            ScrollDirection::Up
+        ));
+    }
+
+    #[test]
+    fn test_step_timeout_wins_over_run_wait() {
+        let defaults = ExecutionOptions::default().wait(None);
+        assert_eq!(defaults.timeout, DEFAULT_WAIT_TIMEOUT);
+        assert_eq!(defaults.interval, DEFAULT_POLL_INTERVAL);
+
+        let options = ExecutionOptions {
+            timeout: Some(Duration::from_secs(10)),
+            poll_interval: Some(Duration::from_millis(100)),
+            ..ExecutionOptions::default()
+        };
+        assert_eq!(options.wait(None).timeout, Duration::from_secs(10));
+        let wait = options.wait(Some(1500));
+        assert_eq!(wait.timeout, Duration::from_millis(1500));
+        assert_eq!(wait.interval, Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_bare_target_matches_text_or_description() {
+        let selectors = element_selectors(&ElementSelector::Text("Login".to_string())).unwrap();
+        assert!(matches!(
+            selectors.as_slice(),
+            [Selector::Text { text }, Selector::Description { description }]
+                if text == "Login" && description == "Login"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_steps_after_a_failure_are_skipped_without_running() {
+        let driver = FakeDriver::failing_on("back");
+        let run = run_flow(
+            &driver,
+            "- pressKey: Enter\n- back\n- hideKeyboard\n- pressKey: Home\n",
+            &ExecutionOptions::default(),
+        )
+        .await;
+
+        assert_eq!(
+            statuses(&run),
+            vec![
+                StepStatus::Passed,
+                StepStatus::Failed,
+                StepStatus::Skipped,
+                StepStatus::Skipped
+            ]
+        );
+        assert_eq!(run.status, RunStatus::Failed);
+        assert_eq!(
+            run.steps[2].message.as_deref(),
+            Some("A previous step failed")
+        );
+        // Only the error screenshot follows the failing call
+        assert_eq!(driver.calls(), vec!["pressKey Enter", "back", "screenshot"]);
+    }
+
+    #[tokio::test]
+    async fn test_capture_steps_attaches_a_screenshot_to_every_step() {
+        let dir = std::env::temp_dir().join("rust_pilot_capture_steps");
+        let _ = std::fs::remove_dir_all(&dir);
+        let driver = FakeDriver {
+            fail_on: Some("hideKeyboard"),
+            screen: Some(b"png"),
+            ..FakeDriver::default()
+        };
+        let options = ExecutionOptions {
+            report_dir: Some(dir.clone()),
+            capture_steps: true,
+            ..ExecutionOptions::default()
+        };
+        let run = run_flow(
+            &driver,
+            "- pressKey: Enter\n- back\n- hideKeyboard\n",
+            &options,
+        )
+        .await;
+
+        assert_eq!(
+            driver.calls(),
+            vec![
+                "pressKey Enter",
+                "screenshot",
+                "back",
+                "screenshot",
+                "hideKeyboard",
+                "screenshot"
+            ]
+        );
+        for step in &run.steps {
+            let screenshots: Vec<&str> = step.screenshots().collect();
+            assert_eq!(screenshots.len(), 1, "{}", step.description);
+            // Paths are relative to the report directory, so report links resolve
+            assert!(screenshots[0].starts_with("screenshots/"));
+            assert_eq!(std::fs::read(dir.join(screenshots[0])).unwrap(), b"png");
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_scroll_stops_at_the_end_of_the_content() {
+        let driver = FakeDriver::default();
+        let run = run_flow(
+            &driver,
+            "- scrollUntilVisible:\n    element: Footer\n    timeout: 60000\n",
+            &ExecutionOptions::default(),
+        )
+        .await;
+
+        assert_eq!(statuses(&run), vec![StepStatus::Failed]);
+        let message = run.steps[0].message.as_deref().unwrap();
+        assert!(message.contains("Reached the end of the content without finding Footer"));
+        // The unchanged source after the first swipe ends the scroll long before the timeout
+        let swipes = driver
+            .calls()
+            .iter()
+            .filter(|call| *call == "swipe")
+            .count();
+        assert_eq!(swipes, 1);
+    }
+
+    #[tokio::test]
+    async fn test_waits_poll_until_the_step_timeout() {
+        let driver = FakeDriver::default();
+        let options = ExecutionOptions {
+            poll_interval: Some(Duration::from_millis(10)),
+            ..ExecutionOptions::default()
+        };
+        let started = std::time::Instant::now();
+        let run = run_flow(
+            &driver,
+            "- extendedWaitUntil:\n    visible: Welcome\n    timeout: 60\n",
+            &options,
+        )
+        .await;
+
+        assert!(started.elapsed() >= Duration::from_millis(60));
+        assert_eq!(statuses(&run), vec![StepStatus::Failed]);
+        assert_eq!(
+            run.steps[0].message.as_deref(),
+            Some("Timed out: Welcome not visible after 60 ms")
+        );
+        // A bare string is looked up as a text and as a description on every check
+        let lookups = driver.calls().iter().filter(|call| *call == "find").count();
+        assert!(lookups >= 6, "only {} lookups", lookups);
+    }
+
+    #[tokio::test]
+    async fn test_state_check_errors_fail_the_step() {
+        let driver = FakeDriver {
+            fail_on: Some("state"),
+            session: Some(fake_session().await),
+            ..FakeDriver::default()
+        };
+        let run = run_flow(
+            &driver,
+            "- tapOn:\n    id: submit\n    enabled: true\n",
+            &ExecutionOptions::default(),
+        )
+        .await;
+
+        assert_eq!(statuses(&run), vec![StepStatus::Failed]);
+        let message = run.steps[0].message.as_deref().unwrap();
+        assert!(message.contains("fake failure"), "{}", message);
+        // The error ends the wait at once instead of reading as a missing element
+        assert_eq!(
+            driver.calls(),
+            vec!["find", "visible", "state", "screenshot"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stale_elements_count_as_not_shown() {
+        let driver = FakeDriver {
+            session: Some(fake_session().await),
+            ..FakeDriver::default()
+        };
+        let options = ExecutionOptions {
+            poll_interval: Some(Duration::from_millis(10)),
+            ..ExecutionOptions::default()
+        };
+        let run = run_flow(
+            &driver,
+            "- tapOn:\n    id: submit\n    enabled: true\n    timeout: 50\n",
+            &options,
+        )
+        .await;
+
+        assert_eq!(statuses(&run), vec![StepStatus::Failed]);
+        assert_eq!(
+            run.steps[0].message.as_deref(),
+            Some("Element not found: id=submit")
+        );
+        let lookups = driver.calls().iter().filter(|call| *call == "find").count();
+        assert!(lookups >= 2, "only {} lookups", lookups);
+    }
+
+    #[tokio::test]
+    async fn test_retries_only_repeat_steps_without_side_effects() {
+        let options = ExecutionOptions {
+            retries: 2,
+            ..ExecutionOptions::default()
+        };
+        let driver = FakeDriver::failing_on("pressKey Enter");
+        let run = run_flow(&driver, "- pressKey: Enter\n", &options).await;
+        assert_eq!(statuses(&run), vec![StepStatus::Failed]);
+        assert_eq!(driver.calls(), vec!["pressKey Enter", "screenshot"]);
+
+        let driver = FakeDriver::failing_on("find");
+        let run = run_flow(&driver, "- assertVisible: Welcome\n", &options).await;
+        assert_eq!(statuses(&run), vec![StepStatus::Failed]);
+        let lookups = driver.calls().iter().filter(|call| *call == "find").count();
+        assert_eq!(lookups, 3);
+    }
+}
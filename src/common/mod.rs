@@ -3,13 +3,23 @@
 //! This module contains shared models, utilities, and step handling
 //! functionality used by all platform-specific implementations.
 
+pub mod context;
+pub mod driver;
+pub mod executor;
 pub mod models;
+pub mod session;
 pub mod steps;
 pub mod tags;
 pub mod utils;
+pub mod variables;
 
 // Re-export commonly used items for convenience
+pub use context::*;
+pub use driver::*;
+pub use executor::*;
 pub use models::*;
+pub use session::*;
 pub use steps::*;
 pub use tags::*;
 pub use utils::*;
+pub use variables::*;
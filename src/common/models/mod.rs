@@ -1,6 +1,17 @@
-use std::process;
-use serde::{Deserialize, Serialize};
-use crate::common::tags::error_tag;
+#[cfg(test)]
+mod test;
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::error::{Result, RustPilotError};
+use crate::secrets;
+
+/// Variables of an `env:` block, by name
+pub type Env = BTreeMap<String, String>;
 
 /// Header information for test files
 #[derive(Debug, Serialize, Deserialize)]
@@ -8,6 +19,22 @@ use crate::common::tags::error_tag;
 pub struct TestFileHeader {
     pub appId: Option<String>,
     pub tags: Option<Vec<String>>,
+    /// Default values of the flow's variables
+    #[serde(default, deserialize_with = "deserialize_env")]
+    pub env: Option<Env>,
+}
+
+impl TestFileHeader {
+    /// Whether a flow with these tags is selected by the include/exclude filters
+    ///
+    /// An empty `include` selects every flow; any tag in `exclude` wins over `include`.
+    pub fn matches_tags(&self, include: &[String], exclude: &[String]) -> bool {
+        let tags = self.tags.as_deref().unwrap_or_default();
+        if tags.iter().any(|tag| exclude.contains(tag)) {
+            return false;
+        }
+        include.is_empty() || tags.iter().any(|tag| include.contains(tag))
+    }
 }
 
 /// Capabilities file structure
@@ -15,11 +42,57 @@ pub struct TestFileHeader {
 pub struct CapsFile {
     pub platform: Platform,
     pub app_path: String,
+    #[serde(default)]
     pub full_reset: bool,
     pub platform_version: String,
+    pub automation_name: Option<String>,
     pub custom_caps: Option<Vec<CustomCapability>>,
 }
 
+impl CapsFile {
+    /// W3C Appium capabilities equivalent to this caps file
+    ///
+    /// Custom caps are applied last, so they can override the other fields.
+    pub fn capabilities(&self) -> Result<HashMap<String, Value>> {
+        let platform_name = match self.platform {
+            Platform::Android => "Android",
+            Platform::Ios => "iOS",
+            Platform::Flutter => {
+                return Err(RustPilotError::Config(
+                    "platform: flutter needs the device platform, \
+                     use android or ios with automation_name: Flutter"
+                        .to_string(),
+                ))
+            }
+        };
+        let mut capabilities = HashMap::from([
+            ("platformName".to_string(), Value::from(platform_name)),
+            (
+                "appium:app".to_string(),
+                Value::from(self.app_path.as_str()),
+            ),
+            (
+                "platformVersion".to_string(),
+                Value::from(self.platform_version.as_str()),
+            ),
+            ("appium:fullReset".to_string(), Value::from(self.full_reset)),
+        ]);
+        if let Some(automation_name) = &self.automation_name {
+            capabilities.insert(
+                "appium:automationName".to_string(),
+                Value::from(automation_name.as_str()),
+            );
+        }
+        for custom_capability in self.custom_caps.iter().flatten() {
+            let value = custom_capability.value.to_value();
+            if !value.is_null() {
+                capabilities.insert(custom_capability.key.clone(), value);
+            }
+        }
+        Ok(capabilities)
+    }
+}
+
 /// Step file container
 #[derive(Debug, Serialize, Deserialize)]
 pub struct StepFile(pub Vec<Step>);
@@ -29,14 +102,182 @@ pub struct StepFile(pub Vec<Step>);
 #[serde(untagged)]
 #[allow(non_snake_case)]
 pub enum Step {
-    RunFlow { runFlow: String },
-    TapOn { tapOn: TapOn },
-    RunScript { runScript: String },
-    InputText { inputText: String },
-    AssertVisible { assertVisible: String },
-    AssertNotVisible { assertNotVisible: String },
-    LaunchApp { launchApp: LaunchApp },
-    Swipe { swipe: SwipeOptions },
+    RunFlow {
+        runFlow: RunFlow,
+    },
+    TapOn {
+        tapOn: ElementSelector,
+    },
+    RunScript {
+        runScript: String,
+    },
+    InputText {
+        inputText: InputText,
+    },
+    AssertVisible {
+        assertVisible: ElementSelector,
+    },
+    AssertNotVisible {
+        assertNotVisible: ElementSelector,
+    },
+    LaunchApp {
+        #[serde(deserialize_with = "null_as_default")]
+        launchApp: LaunchApp,
+    },
+    StopApp {
+        /// App to stop instead of the flow's `appId`
+        #[serde(deserialize_with = "null_as_default")]
+        stopApp: Option<String>,
+    },
+    Swipe {
+        swipe: SwipeOptions,
+    },
+    ScrollUntilVisible {
+        scrollUntilVisible: ScrollUntilVisible,
+    },
+    ExtendedWaitUntil {
+        extendedWaitUntil: ExtendedWaitUntil,
+    },
+    PressKey {
+        pressKey: Key,
+    },
+    Back {
+        back: (),
+    },
+    HideKeyboard {
+        hideKeyboard: (),
+    },
+    EraseText {
+        /// Characters to erase; [`DEFAULT_ERASE_COUNT`] when unset
+        #[serde(deserialize_with = "null_as_default")]
+        eraseText: Option<usize>,
+    },
+}
+
+/// Characters `eraseText` removes when no count is given
+pub const DEFAULT_ERASE_COUNT: usize = 50;
+
+impl Step {
+    /// Maestro command name of the step, as written in the flow file
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Step::RunFlow { .. } => "runFlow",
+            Step::TapOn { .. } => "tapOn",
+            Step::RunScript { .. } => "runScript",
+            Step::InputText { .. } => "inputText",
+            Step::AssertVisible { .. } => "assertVisible",
+            Step::AssertNotVisible { .. } => "assertNotVisible",
+            Step::LaunchApp { .. } => "launchApp",
+            Step::StopApp { .. } => "stopApp",
+            Step::Swipe { .. } => "swipe",
+            Step::ScrollUntilVisible { .. } => "scrollUntilVisible",
+            Step::ExtendedWaitUntil { .. } => "extendedWaitUntil",
+            Step::PressKey { .. } => "pressKey",
+            Step::Back { .. } => "back",
+            Step::HideKeyboard { .. } => "hideKeyboard",
+            Step::EraseText { .. } => "eraseText",
+        }
+    }
+
+    /// Whether running the step again can't repeat a side effect, so it may be retried
+    ///
+    /// Only element lookups and assertions qualify: typing, erasing, key presses
+    /// and app commands would happen twice.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            Step::TapOn { .. }
+                | Step::AssertVisible { .. }
+                | Step::AssertNotVisible { .. }
+                | Step::ScrollUntilVisible { .. }
+                | Step::ExtendedWaitUntil { .. }
+        )
+    }
+
+    /// Short human readable summary, used in logs and reports
+    pub fn description(&self) -> String {
+        match self {
+            Step::RunFlow { runFlow } => format!("Run flow {}", runFlow.file()),
+            Step::TapOn { tapOn } => format!("Tap on {}", tapOn.label()),
+            Step::RunScript { runScript } => format!("Run script {}", runScript),
+            Step::InputText { inputText } if inputText.is_secret() => {
+                format!("Input text {}", secrets::MASK)
+            }
+            Step::InputText { inputText } => format!("Input text {}", inputText.text()),
+            Step::AssertVisible { assertVisible } => {
+                format!("Assert {} visible", assertVisible.label())
+            }
+            Step::AssertNotVisible { assertNotVisible } => {
+                format!("Assert {} not visible", assertNotVisible.label())
+            }
+            Step::LaunchApp { launchApp } => match &launchApp.appId {
+                Some(app_id) => {
+                    format!(
+                        "Launch app {} (clear state: {})",
+                        app_id, launchApp.clearState
+                    )
+                }
+                None => format!("Launch app (clear state: {})", launchApp.clearState),
+            },
+            Step::StopApp { stopApp } => match stopApp {
+                Some(app_id) => format!("Stop app {}", app_id),
+                None => "Stop app".to_string(),
+            },
+            Step::Swipe { swipe } => format!("Swipe from {} to {}", swipe.start.0, swipe.end.0),
+            Step::ScrollUntilVisible { scrollUntilVisible } => format!(
+                "Scroll {} until {} is visible",
+                scrollUntilVisible.direction.name(),
+                scrollUntilVisible.element.label()
+            ),
+            Step::ExtendedWaitUntil { extendedWaitUntil } => match &extendedWaitUntil.condition {
+                WaitCondition::Visible(element) => {
+                    format!("Wait until {} is visible", element.label())
+                }
+                WaitCondition::NotVisible(element) => {
+                    format!("Wait until {} is not visible", element.label())
+                }
+            },
+            Step::PressKey { pressKey } => format!("Press key {}", pressKey.name()),
+            Step::Back { .. } => "Go back".to_string(),
+            Step::HideKeyboard { .. } => "Hide keyboard".to_string(),
+            Step::EraseText { eraseText } => format!(
+                "Erase {} characters",
+                eraseText.unwrap_or(DEFAULT_ERASE_COUNT)
+            ),
+        }
+    }
+}
+
+/// Position of a step in its flow file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    #[serde(default)]
+    pub column: usize,
+    /// Flow files from the top-level one down to `file`
+    #[serde(default)]
+    pub include_chain: Vec<String>,
+    /// How many `runFlow`s deep the step is; 0 for the top-level flow
+    #[serde(default)]
+    pub depth: usize,
+}
+
+impl SourceLocation {
+    /// `main.yml -> login.yml`, or `None` for a step of the top-level flow
+    pub fn included_from(&self) -> Option<String> {
+        (self.include_chain.len() > 1).then(|| self.include_chain.join(" -> "))
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)?;
+        if let Some(chain) = self.included_from() {
+            write!(f, " (via {})", chain)?;
+        }
+        Ok(())
+    }
 }
 
 /// Swipe options for gesture actions
@@ -52,65 +293,501 @@ pub struct ScreenPercentages(pub String);
 
 impl ScreenPercentages {
     /// Converts percentage string to float coordinates
-    pub fn to_f64(&self) -> (f64, f64) {
-        let numbers: Vec<f64> = self
+    pub fn to_f64(&self) -> Result<(f64, f64)> {
+        let numbers = self
             .0
             .split(",")
             .map(|s| s.trim_end_matches("%"))
             .map(|s| s.trim())
             .map(|number| {
-                number.parse::<f64>().unwrap_or_else(|err| {
-                    eprintln!(
-                        "{} Error: Swipe percentage must be a number: {:#?}, {}",
-                        error_tag(),
-                        number,
-                        err
-                    );
-                    process::exit(1);
+                number.parse::<f64>().map_err(|err| {
+                    RustPilotError::parse(
+                        "swipe percentage",
+                        format!("must be a number: {:?}, {}", number, err),
+                    )
                 })
             })
-            .collect();
+            .collect::<Result<Vec<f64>>>()?;
 
         if numbers.len() != 2 {
-            eprintln!(
-                "{} Error: Swipe percentage must have two values: x and y",
-                error_tag()
-            );
-            process::exit(1);
+            return Err(RustPilotError::parse(
+                "swipe percentage",
+                "must have two values: x and y",
+            ));
         }
 
-        for number in numbers.clone() {
-            if number > 100.0 || number < 0.0 {
-                eprintln!(
-                    "{} Error: Swipe percentage must be between 0 and 100",
-                    error_tag()
-                );
-                process::exit(1);
+        for number in numbers.iter() {
+            if !(0.0..=100.0).contains(number) {
+                return Err(RustPilotError::parse(
+                    "swipe percentage",
+                    "must be between 0 and 100",
+                ));
             }
         }
-        (numbers[0], numbers[1])
+        Ok((numbers[0], numbers[1]))
     }
 }
 
-/// Launch app configuration
+/// Scrolls until an element shows up, as in Maestro's `scrollUntilVisible`
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[allow(non_snake_case)]
+pub struct ScrollUntilVisible {
+    /// Element to look for, written like the target of `tapOn`
+    pub element: ElementSelector,
+    #[serde(default)]
+    pub direction: ScrollDirection,
+    /// Milliseconds to keep scrolling before failing
+    #[serde(default = "default_scroll_timeout")]
+    pub timeout: u64,
+    /// From 0 (slowest) to 100 (fastest)
+    #[serde(default = "default_scroll_speed")]
+    pub speed: u32,
+    /// Share of the element, in percent, that must be on screen
+    #[serde(default = "default_visibility_percentage")]
+    pub visibilityPercentage: u32,
+    /// Keep scrolling until the element is near the middle of the screen
+    #[serde(default)]
+    pub centerElement: bool,
+}
+
+fn default_scroll_timeout() -> u64 {
+    20_000
+}
+
+fn default_scroll_speed() -> u32 {
+    40
+}
+
+fn default_visibility_percentage() -> u32 {
+    100
+}
+
+/// Waits for an element to show up or go away, as in Maestro's `extendedWaitUntil`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(try_from = "RawExtendedWaitUntil", into = "RawExtendedWaitUntil")]
+pub struct ExtendedWaitUntil {
+    pub condition: WaitCondition,
+    /// Milliseconds; the run's wait timeout when unset
+    pub timeout: Option<u64>,
+}
+
+/// What an `extendedWaitUntil` waits for
+#[derive(Debug, Clone)]
+pub enum WaitCondition {
+    Visible(ElementSelector),
+    NotVisible(ElementSelector),
+}
+
+impl WaitCondition {
+    pub fn element(&self) -> &ElementSelector {
+        match self {
+            WaitCondition::Visible(element) | WaitCondition::NotVisible(element) => element,
+        }
+    }
+}
+
+/// `extendedWaitUntil` as written in flow files, with `visible` and `notVisible` side by side
+#[derive(Serialize, Deserialize)]
+#[allow(non_snake_case)]
+struct RawExtendedWaitUntil {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    visible: Option<ElementSelector>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notVisible: Option<ElementSelector>,
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+/// Accepts exactly one of `visible` and `notVisible`
+impl TryFrom<RawExtendedWaitUntil> for ExtendedWaitUntil {
+    type Error = String;
+
+    fn try_from(raw: RawExtendedWaitUntil) -> std::result::Result<Self, Self::Error> {
+        let condition = match (raw.visible, raw.notVisible) {
+            (Some(element), None) => WaitCondition::Visible(element),
+            (None, Some(element)) => WaitCondition::NotVisible(element),
+            _ => return Err("expected exactly one of visible or notVisible".to_string()),
+        };
+        Ok(ExtendedWaitUntil {
+            condition,
+            timeout: raw.timeout,
+        })
+    }
+}
+
+impl From<ExtendedWaitUntil> for RawExtendedWaitUntil {
+    fn from(wait: ExtendedWaitUntil) -> Self {
+        let (visible, not_visible) = match wait.condition {
+            WaitCondition::Visible(element) => (Some(element), None),
+            WaitCondition::NotVisible(element) => (None, Some(element)),
+        };
+        RawExtendedWaitUntil {
+            visible,
+            notVisible: not_visible,
+            timeout: wait.timeout,
+        }
+    }
+}
+
+/// Keys and device buttons `pressKey` can press, named as in Maestro
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub enum Key {
+    Enter,
+    Backspace,
+    Back,
+    Home,
+    Lock,
+    Power,
+    Tab,
+    Escape,
+    VolumeUp,
+    VolumeDown,
+    RemoteDpadUp,
+    RemoteDpadDown,
+    RemoteDpadLeft,
+    RemoteDpadRight,
+    RemoteDpadCenter,
+}
+
+impl Key {
+    pub const ALL: [Key; 15] = [
+        Key::Enter,
+        Key::Backspace,
+        Key::Back,
+        Key::Home,
+        Key::Lock,
+        Key::Power,
+        Key::Tab,
+        Key::Escape,
+        Key::VolumeUp,
+        Key::VolumeDown,
+        Key::RemoteDpadUp,
+        Key::RemoteDpadDown,
+        Key::RemoteDpadLeft,
+        Key::RemoteDpadRight,
+        Key::RemoteDpadCenter,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Key::Enter => "Enter",
+            Key::Backspace => "Backspace",
+            Key::Back => "Back",
+            Key::Home => "Home",
+            Key::Lock => "Lock",
+            Key::Power => "Power",
+            Key::Tab => "Tab",
+            Key::Escape => "Escape",
+            Key::VolumeUp => "Volume Up",
+            Key::VolumeDown => "Volume Down",
+            Key::RemoteDpadUp => "Remote Dpad Up",
+            Key::RemoteDpadDown => "Remote Dpad Down",
+            Key::RemoteDpadLeft => "Remote Dpad Left",
+            Key::RemoteDpadRight => "Remote Dpad Right",
+            Key::RemoteDpadCenter => "Remote Dpad Center",
+        }
+    }
+}
+
+/// Reads a key name ignoring case, spaces, dashes and underscores, so `volume_up` is `Volume Up`
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(name: String) -> std::result::Result<Self, Self::Error> {
+        let normalize = |name: &str| {
+            name.chars()
+                .filter(|c| !matches!(c, ' ' | '-' | '_'))
+                .collect::<String>()
+                .to_lowercase()
+        };
+        let wanted = normalize(&name);
+        Key::ALL
+            .into_iter()
+            .find(|key| normalize(key.name()) == wanted)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Key::ALL.iter().map(Key::name).collect();
+                format!(
+                    "unknown key {:?}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl From<Key> for String {
+    fn from(key: Key) -> Self {
+        key.name().to_string()
+    }
+}
+
+/// Direction the content moves in; `DOWN` reveals what is below
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ScrollDirection {
+    #[serde(alias = "up")]
+    Up,
+    #[default]
+    #[serde(alias = "down")]
+    Down,
+    #[serde(alias = "left")]
+    Left,
+    #[serde(alias = "right")]
+    Right,
+}
+
+impl ScrollDirection {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ScrollDirection::Up => "up",
+            ScrollDirection::Down => "down",
+            ScrollDirection::Left => "left",
+            ScrollDirection::Right => "right",
+        }
+    }
+}
+
+/// Launch app configuration; `- launchApp` alone relaunches the flow's app
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[allow(non_snake_case)]
 pub struct LaunchApp {
+    /// App to launch instead of the flow's `appId`
+    #[serde(default)]
+    pub appId: Option<String>,
+    /// Clear the app data before launching
+    #[serde(default)]
     pub clearState: bool,
+    /// Stop the app first when it is running; true unless set
+    #[serde(default)]
+    pub stopApp: Option<bool>,
+    /// Permission name (`camera`, `location`, `all`, ...) to the state to set before launching
+    #[serde(default)]
+    pub permissions: Option<BTreeMap<String, PermissionState>>,
+    /// Launch arguments: intent extras on Android, `-key value` arguments on iOS
+    #[serde(default)]
+    pub arguments: Option<serde_json::Map<String, Value>>,
+}
+
+/// State a `launchApp` permission is set to
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionState {
+    Allow,
+    Deny,
+    Unset,
+}
+
+/// Reads a step value that may be left empty, as in `- launchApp:`
+fn null_as_default<'de, D, T>(deserializer: D) -> std::result::Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// RunFlow action variants
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum RunFlow {
+    RunFlowFile(String),
+    RunFlowOption(RunFlowOption),
+}
+
+/// Included flow with variables of its own
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RunFlowOption {
+    pub file: String,
+    /// Overrides the variables of the including flow
+    #[serde(default, deserialize_with = "deserialize_env")]
+    pub env: Option<Env>,
+}
+
+impl RunFlow {
+    /// Path of the included flow, relative to the including one
+    pub fn file(&self) -> &str {
+        match self {
+            RunFlow::RunFlowFile(file) => file,
+            RunFlow::RunFlowOption(options) => &options.file,
+        }
+    }
+
+    pub fn env(&self) -> Option<&Env> {
+        match self {
+            RunFlow::RunFlowFile(_) => None,
+            RunFlow::RunFlowOption(options) => options.env.as_ref(),
+        }
+    }
+}
+
+/// Scalar value of an `env:` entry; numbers and booleans are kept as written
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum EnvValue {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+/// Reads an `env:` block, accepting unquoted numbers and booleans
+fn deserialize_env<'de, D>(deserializer: D) -> std::result::Result<Option<Env>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let env: Option<BTreeMap<String, EnvValue>> = Option::deserialize(deserializer)?;
+    Ok(env.map(|env| {
+        env.into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    EnvValue::Text(value) => value,
+                    EnvValue::Integer(value) => value.to_string(),
+                    EnvValue::Float(value) => value.to_string(),
+                    EnvValue::Boolean(value) => value.to_string(),
+                };
+                (name, value)
+            })
+            .collect()
+    }))
 }
 
-/// TapOn action variants
+/// Element a step targets: a text or description, or a block of selector options
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(untagged)]
-pub enum TapOn {
-    TapOnTextOrDescription(String),
-    TapOnOption(TapOnOption),
+pub enum ElementSelector {
+    Text(String),
+    Options(ElementOptions),
 }
 
-/// Tap options for element selection
+impl ElementSelector {
+    /// The text, or the options set on the block, the step targets, like `id=submit, enabled=true`
+    pub fn label(&self) -> String {
+        let options = match self {
+            ElementSelector::Text(text) => return text.clone(),
+            ElementSelector::Options(options) => options,
+        };
+        let strings = [
+            ("id", &options.id),
+            ("text", &options.text),
+            ("className", &options.className),
+            ("description", &options.description),
+            ("hint", &options.hint),
+            ("tooltip", &options.tooltip),
+        ];
+        let numbers = [
+            ("index", options.index.map(u64::from)),
+            ("instance", options.instance.map(u64::from)),
+            ("timeout", options.timeout),
+        ];
+        let flags = [
+            ("optional", options.optional),
+            ("enabled", options.enabled),
+            ("checked", options.checked),
+            ("focused", options.focused),
+            ("selected", options.selected),
+        ];
+        strings
+            .iter()
+            .filter_map(|(name, value)| value.as_ref().map(|value| format!("{}={}", name, value)))
+            .chain(
+                numbers
+                    .iter()
+                    .filter_map(|(name, value)| value.map(|value| format!("{}={}", name, value))),
+            )
+            .chain(
+                flags
+                    .iter()
+                    .filter_map(|(name, value)| value.map(|value| format!("{}={}", name, value))),
+            )
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Milliseconds to wait for the element, when set on the step
+    pub fn timeout(&self) -> Option<u64> {
+        match self {
+            ElementSelector::Text(_) => None,
+            ElementSelector::Options(options) => options.timeout,
+        }
+    }
+
+    /// Whether a missing element skips the step instead of failing it
+    pub fn is_optional(&self) -> bool {
+        matches!(self, ElementSelector::Options(options) if options.optional == Some(true))
+    }
+
+    /// State filters the element must pass, with the value each one expects
+    pub fn states(&self) -> Vec<(ElementState, bool)> {
+        let ElementSelector::Options(options) = self else {
+            return Vec::new();
+        };
+        [
+            (ElementState::Enabled, options.enabled),
+            (ElementState::Checked, options.checked),
+            (ElementState::Focused, options.focused),
+            (ElementState::Selected, options.selected),
+        ]
+        .into_iter()
+        .filter_map(|(state, expected)| expected.map(|expected| (state, expected)))
+        .collect()
+    }
+}
+
+/// State of an element a selector can filter on
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ElementState {
+    Enabled,
+    Checked,
+    Focused,
+    Selected,
+}
+
+impl ElementState {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ElementState::Enabled => "enabled",
+            ElementState::Checked => "checked",
+            ElementState::Focused => "focused",
+            ElementState::Selected => "selected",
+        }
+    }
+}
+
+/// InputText action variants
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum InputText {
+    InputTextString(String),
+    InputTextOption(InputTextOption),
+}
+
+/// Text to type, optionally kept out of logs and reports
 #[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InputTextOption {
+    pub text: String,
+    #[serde(default)]
+    pub secret: bool,
+}
+
+impl InputText {
+    pub fn text(&self) -> &str {
+        match self {
+            InputText::InputTextString(text) => text,
+            InputText::InputTextOption(options) => &options.text,
+        }
+    }
+
+    pub fn is_secret(&self) -> bool {
+        matches!(self, InputText::InputTextOption(options) if options.secret)
+    }
+}
+
+/// Selector options of an element-targeting step
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[allow(non_snake_case)]
-pub struct TapOnOption {
+pub struct ElementOptions {
     pub id: Option<String>,
     pub text: Option<String>,
     pub optional: Option<bool>,
@@ -119,10 +796,22 @@ pub struct TapOnOption {
     pub className: Option<String>,
     pub description: Option<String>,
     pub hint: Option<String>,
+    pub tooltip: Option<String>,
+    /// Milliseconds to wait for the element; the run's wait timeout when unset
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    #[serde(default)]
+    pub checked: Option<bool>,
+    #[serde(default)]
+    pub focused: Option<bool>,
+    #[serde(default)]
+    pub selected: Option<bool>,
 }
 
 /// Supported platforms
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum Platform {
     Android,
@@ -130,6 +819,17 @@ pub enum Platform {
     Flutter,
 }
 
+impl Platform {
+    /// Human readable platform name, as shown in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Android => "Android",
+            Platform::Ios => "iOS",
+            Platform::Flutter => "Flutter",
+        }
+    }
+}
+
 /// Shared actions across platforms
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum SharedAction {
@@ -151,4 +851,25 @@ pub enum CustomCapabilityValue {
     StringValue(String),
     NumberValue(f64),
     NullValue,
+    ListValue(Vec<Value>),
+    ObjectValue(serde_json::Map<String, Value>),
+}
+
+impl CustomCapabilityValue {
+    /// JSON value of the capability; whole numbers stay integers
+    pub fn to_value(&self) -> Value {
+        match self {
+            CustomCapabilityValue::BooleanValue(value) => Value::from(*value),
+            CustomCapabilityValue::StringValue(value) => Value::from(value.as_str()),
+            CustomCapabilityValue::NumberValue(value)
+                if value.fract() == 0.0 && value.abs() < i64::MAX as f64 =>
+            {
+                Value::from(*value as i64)
+            }
+            CustomCapabilityValue::NumberValue(value) => Value::from(*value),
+            CustomCapabilityValue::NullValue => Value::Null,
+            CustomCapabilityValue::ListValue(values) => Value::Array(values.clone()),
+            CustomCapabilityValue::ObjectValue(map) => Value::Object(map.clone()),
+        }
+    }
 }
@@ -0,0 +1,290 @@
+#[cfg(test)]
+mod tests {
+    use crate::common::models::{
+        ElementSelector, ElementState, Key, PermissionState, ScreenPercentages, ScrollDirection,
+        Step, TestFileHeader, WaitCondition,
+    };
+    use crate::common::utils::deserialize_test_file;
+    use crate::error::RustPilotError;
+
+    #[test]
+    fn test_screen_percentages_to_f64() {
+        let percentages = ScreenPercentages("50%, 90%".to_string());
+        assert_eq!(percentages.to_f64().unwrap(), (50.0, 90.0));
+    }
+
+    #[test]
+    fn test_screen_percentages_not_a_number() {
+        let percentages = ScreenPercentages("half, 90%".to_string());
+        assert!(matches!(
+            percentages.to_f64(),
+            Err(RustPilotError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_screen_percentages_wrong_length() {
+        let percentages = ScreenPercentages("50%".to_string());
+        assert!(percentages.to_f64().is_err());
+    }
+
+    #[test]
+    fn test_screen_percentages_out_of_range() {
+        let percentages = ScreenPercentages("50%, 120%".to_string());
+        assert!(percentages.to_f64().is_err());
+    }
+
+    #[test]
+    fn test_header_matches_tags() {
+        let header = TestFileHeader {
+            appId: None,
+            tags: Some(vec!["smoke".to_string(), "login".to_string()]),
+            env: None,
+        };
+        let tags = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(header.matches_tags(&[], &[]));
+        assert!(header.matches_tags(&tags(&["smoke"]), &[]));
+        assert!(!header.matches_tags(&tags(&["nightly"]), &[]));
+        assert!(!header.matches_tags(&tags(&["smoke"]), &tags(&["login"])));
+
+        let untagged = TestFileHeader {
+            appId: None,
+            tags: None,
+            env: None,
+        };
+        assert!(untagged.matches_tags(&[], &tags(&["slow"])));
+        assert!(!untagged.matches_tags(&tags(&["smoke"]), &[]));
+    }
+
+    #[test]
+    fn test_input_text_secret_flag() {
+        let (_, steps) = deserialize_test_file(
+            "appId: app\n---\n- inputText: hello\n- inputText:\n    text: hunter2\n    secret: true\n",
+        )
+        .unwrap();
+        let inputs: Vec<_> = steps
+            .iter()
+            .map(|step| match step {
+                Step::InputText { inputText } => (inputText.text(), inputText.is_secret()),
+                other => panic!("unexpected step {:?}", other),
+            })
+            .collect();
+        assert_eq!(inputs, vec![("hello", false), ("hunter2", true)]);
+        // Listings print descriptions before any secret value is registered
+        assert_eq!(steps[0].description(), "Input text hello");
+        assert_eq!(steps[1].description(), "Input text ***");
+    }
+
+    #[test]
+    fn test_launch_app_and_stop_app_forms() {
+        let (_, steps) = deserialize_test_file(
+            "appId: app\n---\n- launchApp\n- launchApp:\n- launchApp:\n    appId: com.example.other\n    clearState: true\n    stopApp: false\n    permissions:\n      all: deny\n      camera: allow\n    arguments:\n      isE2E: true\n- stopApp\n- stopApp: com.example.other\n",
+        )
+        .unwrap();
+        assert_eq!(steps.len(), 5);
+
+        for step in &steps[..2] {
+            let Step::LaunchApp { launchApp } = step else {
+                panic!("expected a launchApp step, got {:?}", step);
+            };
+            assert!(launchApp.appId.is_none());
+            assert!(!launchApp.clearState);
+        }
+        let Step::LaunchApp { launchApp } = &steps[2] else {
+            panic!("expected a launchApp step, got {:?}", steps[2]);
+        };
+        assert_eq!(launchApp.appId.as_deref(), Some("com.example.other"));
+        assert!(launchApp.clearState);
+        assert_eq!(launchApp.stopApp, Some(false));
+        let permissions = launchApp.permissions.as_ref().unwrap();
+        assert_eq!(permissions.get("all"), Some(&PermissionState::Deny));
+        assert_eq!(permissions.get("camera"), Some(&PermissionState::Allow));
+        assert_eq!(
+            launchApp.arguments.as_ref().unwrap()["isE2E"],
+            serde_json::json!(true)
+        );
+        assert_eq!(
+            steps[2].description(),
+            "Launch app com.example.other (clear state: true)"
+        );
+
+        assert!(matches!(&steps[3], Step::StopApp { stopApp: None }));
+        assert_eq!(steps[4].description(), "Stop app com.example.other");
+        assert!(deserialize_test_file("appId: app\n---\n- notACommand\n").is_err());
+    }
+
+    #[test]
+    fn test_scroll_until_visible_defaults_and_options() {
+        let (_, steps) = deserialize_test_file(
+            "appId: app\n---\n- scrollUntilVisible:\n    element: Footer\n- scrollUntilVisible:\n    element:\n      id: footer\n    direction: up\n    timeout: 5000\n    speed: 80\n    visibilityPercentage: 50\n    centerElement: true\n",
+        )
+        .unwrap();
+
+        let Step::ScrollUntilVisible { scrollUntilVisible } = &steps[0] else {
+            panic!("expected a scrollUntilVisible step, got {:?}", steps[0]);
+        };
+        assert!(
+            matches!(&scrollUntilVisible.element, ElementSelector::Text(text) if text == "Footer")
+        );
+        assert_eq!(scrollUntilVisible.direction, ScrollDirection::Down);
+        assert_eq!(
+            (
+                scrollUntilVisible.timeout,
+                scrollUntilVisible.speed,
+                scrollUntilVisible.visibilityPercentage
+            ),
+            (20_000, 40, 100)
+        );
+        assert!(!scrollUntilVisible.centerElement);
+        assert_eq!(
+            steps[0].description(),
+            "Scroll down until Footer is visible"
+        );
+
+        let Step::ScrollUntilVisible { scrollUntilVisible } = &steps[1] else {
+            panic!("expected a scrollUntilVisible step, got {:?}", steps[1]);
+        };
+        assert!(
+            matches!(&scrollUntilVisible.element, ElementSelector::Options(options) if options.id.as_deref() == Some("footer"))
+        );
+        assert_eq!(scrollUntilVisible.direction, ScrollDirection::Up);
+        assert_eq!(
+            (
+                scrollUntilVisible.timeout,
+                scrollUntilVisible.speed,
+                scrollUntilVisible.visibilityPercentage
+            ),
+            (5000, 80, 50)
+        );
+        assert!(scrollUntilVisible.centerElement);
+    }
+
+    #[test]
+    fn test_extended_wait_until_and_step_timeout() {
+        let (_, steps) = deserialize_test_file(
+            "appId: app\n---\n- extendedWaitUntil:\n    visible: Welcome\n    timeout: 10000\n- extendedWaitUntil:\n    notVisible:\n      id: spinner\n- tapOn:\n    id: submit\n    timeout: 2000\n",
+        )
+        .unwrap();
+
+        let Step::ExtendedWaitUntil { extendedWaitUntil } = &steps[0] else {
+            panic!("expected an extendedWaitUntil step, got {:?}", steps[0]);
+        };
+        assert_eq!(extendedWaitUntil.timeout, Some(10_000));
+        assert_eq!(steps[0].description(), "Wait until Welcome is visible");
+        let Step::ExtendedWaitUntil { extendedWaitUntil } = &steps[1] else {
+            panic!("expected an extendedWaitUntil step, got {:?}", steps[1]);
+        };
+        assert!(extendedWaitUntil.timeout.is_none());
+        assert!(
+            matches!(&extendedWaitUntil.condition, WaitCondition::NotVisible(ElementSelector::Options(options)) if options.id.as_deref() == Some("spinner"))
+        );
+        assert!(
+            matches!(&steps[2], Step::TapOn { tapOn: ElementSelector::Options(options) } if options.timeout == Some(2000))
+        );
+
+        // Survives the JSON round trip of variable interpolation
+        let json = serde_json::to_value(&steps[1]).unwrap();
+        assert!(json["extendedWaitUntil"].get("visible").is_none());
+        let step: Step = serde_json::from_value(json).unwrap();
+        assert_eq!(step.description(), steps[1].description());
+    }
+
+    #[test]
+    fn test_extended_wait_until_needs_exactly_one_condition() {
+        let neither = "appId: app\n---\n- extendedWaitUntil:\n    timeout: 1000\n";
+        let both = "appId: app\n---\n- extendedWaitUntil:\n    visible: Welcome\n    notVisible: Loading\n";
+        assert!(matches!(
+            deserialize_test_file(neither),
+            Err(RustPilotError::Parse { .. })
+        ));
+        assert!(matches!(
+            deserialize_test_file(both),
+            Err(RustPilotError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_key_and_keyboard_steps() {
+        let (_, steps) = deserialize_test_file(
+            "appId: app\n---\n- pressKey: Enter\n- pressKey: volume_up\n- back\n- hideKeyboard\n- eraseText\n- eraseText: 5\n",
+        )
+        .unwrap();
+
+        assert!(matches!(
+            steps[0],
+            Step::PressKey {
+                pressKey: Key::Enter
+            }
+        ));
+        assert!(matches!(
+            steps[1],
+            Step::PressKey {
+                pressKey: Key::VolumeUp
+            }
+        ));
+        assert_eq!(steps[1].description(), "Press key Volume Up");
+        assert!(matches!(steps[2], Step::Back { .. }));
+        assert!(matches!(steps[3], Step::HideKeyboard { .. }));
+        assert!(matches!(steps[4], Step::EraseText { eraseText: None }));
+        assert_eq!(steps[4].description(), "Erase 50 characters");
+        assert!(matches!(steps[5], Step::EraseText { eraseText: Some(5) }));
+
+        // Keys survive the JSON round trip of variable interpolation
+        let json = serde_json::to_value(&steps[1]).unwrap();
+        assert_eq!(json, serde_json::json!({ "pressKey": "Volume Up" }));
+        assert!(deserialize_test_file("appId: app\n---\n- pressKey: Shift\n").is_err());
+    }
+
+    #[test]
+    fn test_element_selector_shorthand_and_state_filters() {
+        let (_, steps) = deserialize_test_file(
+            "appId: app\n---\n- assertVisible: Welcome\n- assertVisible:\n    id: submit\n    enabled: true\n    checked: false\n- assertNotVisible:\n    text: Remember me\n    selected: true\n",
+        )
+        .unwrap();
+
+        let Step::AssertVisible { assertVisible } = &steps[0] else {
+            panic!("expected an assertVisible step, got {:?}", steps[0]);
+        };
+        assert!(matches!(assertVisible, ElementSelector::Text(text) if text == "Welcome"));
+        assert!(assertVisible.states().is_empty());
+
+        let Step::AssertVisible { assertVisible } = &steps[1] else {
+            panic!("expected an assertVisible step, got {:?}", steps[1]);
+        };
+        assert_eq!(
+            assertVisible.states(),
+            vec![
+                (ElementState::Enabled, true),
+                (ElementState::Checked, false)
+            ]
+        );
+
+        let Step::AssertNotVisible { assertNotVisible } = &steps[2] else {
+            panic!("expected an assertNotVisible step, got {:?}", steps[2]);
+        };
+        assert_eq!(
+            assertNotVisible.states(),
+            vec![(ElementState::Selected, true)]
+        );
+        assert!(deserialize_test_file(
+            "appId: app\n---\n- assertVisible:\n    id: submit\n    enabled: yes please\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_option_labels_list_only_the_set_fields() {
+        let (_, steps) = deserialize_test_file(
+            "appId: app\n---\n- tapOn:\n    id: submit\n    enabled: true\n- tapOn: Login\n",
+        )
+        .unwrap();
+        assert_eq!(steps[0].description(), "Tap on id=submit, enabled=true");
+        assert_eq!(steps[1].description(), "Tap on Login");
+    }
+}
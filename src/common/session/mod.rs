@@ -0,0 +1,301 @@
+//! Appium server settings and session creation
+//!
+//! Server settings are taken from the command line first, then from
+//! `RUST_PILOT_*` environment variables, then from `rustPilot:*` keys of the
+//! caps file, falling back to a local Appium 2 server.
+
+#[cfg(test)]
+mod test;
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use appium_client::capabilities::AppiumCapability;
+use appium_client::{Client, ClientBuilder};
+use fantoccini::error::NewSessionError;
+use serde_json::{Number, Value};
+
+use crate::error::{Result, RustPilotError};
+use crate::logger::Logger;
+
+/// Appium server used when none is configured
+pub const DEFAULT_APPIUM_URL: &str = "http://localhost:4723/";
+/// How long a single session request may take, app installation included
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(120);
+/// How many times a failed session request is retried
+pub const DEFAULT_CONNECT_RETRIES: u32 = 2;
+/// Wait before the first retry; doubled after every attempt
+pub const DEFAULT_CONNECT_BACKOFF: Duration = Duration::from_secs(1);
+/// Longest wait between two attempts
+const MAX_CONNECT_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Prefix of caps file keys read by rust_pilot and never sent to Appium
+pub const CAPS_PREFIX: &str = "rustPilot:";
+/// Appium capability set from [`ServerOptions::new_command_timeout`]
+pub const NEW_COMMAND_TIMEOUT_CAP: &str = "appium:newCommandTimeout";
+
+pub const APPIUM_URL_CAP: &str = "rustPilot:appiumUrl";
+pub const BASE_PATH_CAP: &str = "rustPilot:basePath";
+pub const CONNECT_TIMEOUT_CAP: &str = "rustPilot:connectTimeout";
+pub const CONNECT_RETRIES_CAP: &str = "rustPilot:connectRetries";
+
+const APPIUM_URL_ENV: &str = "RUST_PILOT_APPIUM_URL";
+const BASE_PATH_ENV: &str = "RUST_PILOT_APPIUM_BASE_PATH";
+const CONNECT_TIMEOUT_ENV: &str = "RUST_PILOT_CONNECT_TIMEOUT";
+const NEW_COMMAND_TIMEOUT_ENV: &str = "RUST_PILOT_NEW_COMMAND_TIMEOUT";
+const CONNECT_RETRIES_ENV: &str = "RUST_PILOT_CONNECT_RETRIES";
+
+/// Where the Appium server is and how hard to try reaching it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerOptions {
+    pub url: String,
+    /// Path the server is mounted on, such as `/wd/hub` for Appium 1
+    pub base_path: Option<String>,
+    pub connect_timeout: Duration,
+    /// Seconds Appium waits for a command before ending the session
+    pub new_command_timeout: Option<u64>,
+    pub connect_retries: u32,
+    pub connect_backoff: Duration,
+}
+
+/// Server settings given on the command line; they win over env and caps
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerOverrides {
+    pub url: Option<String>,
+    pub base_path: Option<String>,
+    /// Seconds
+    pub connect_timeout: Option<u64>,
+    /// Seconds
+    pub new_command_timeout: Option<u64>,
+    pub connect_retries: Option<u32>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        ServerOptions {
+            url: DEFAULT_APPIUM_URL.to_string(),
+            base_path: None,
+            connect_timeout: DEFAULT_CONNECT_TIMEOUT,
+            new_command_timeout: None,
+            connect_retries: DEFAULT_CONNECT_RETRIES,
+            connect_backoff: DEFAULT_CONNECT_BACKOFF,
+        }
+    }
+}
+
+impl ServerOptions {
+    /// Resolves every setting from `overrides`, then `env`, then `capabilities`
+    pub fn resolve(
+        overrides: &ServerOverrides,
+        capabilities: &HashMap<String, Value>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
+        let defaults = ServerOptions::default();
+        let url = match &overrides.url {
+            Some(url) => Some(url.clone()),
+            None => env(APPIUM_URL_ENV).or(caps_str(capabilities, APPIUM_URL_CAP)?),
+        };
+        let base_path = match &overrides.base_path {
+            Some(base_path) => Some(base_path.clone()),
+            None => env(BASE_PATH_ENV).or(caps_str(capabilities, BASE_PATH_CAP)?),
+        };
+        let connect_timeout = match overrides.connect_timeout {
+            Some(seconds) => Some(seconds),
+            None => setting(&env, CONNECT_TIMEOUT_ENV, capabilities, CONNECT_TIMEOUT_CAP)?,
+        };
+        let new_command_timeout = match overrides.new_command_timeout {
+            Some(seconds) => Some(seconds),
+            None => setting(
+                &env,
+                NEW_COMMAND_TIMEOUT_ENV,
+                capabilities,
+                NEW_COMMAND_TIMEOUT_CAP,
+            )?,
+        };
+        let connect_retries = match overrides.connect_retries {
+            Some(retries) => Some(retries),
+            None => setting(&env, CONNECT_RETRIES_ENV, capabilities, CONNECT_RETRIES_CAP)?
+                .map(|retries| u32::try_from(retries).unwrap_or(u32::MAX)),
+        };
+
+        Ok(ServerOptions {
+            url: url.unwrap_or(defaults.url),
+            base_path: base_path.filter(|path| !path.trim_matches('/').is_empty()),
+            connect_timeout: connect_timeout
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.connect_timeout),
+            new_command_timeout,
+            connect_retries: connect_retries.unwrap_or(defaults.connect_retries),
+            connect_backoff: defaults.connect_backoff,
+        })
+    }
+
+    /// WebDriver endpoint: the URL joined with the base path, ending with `/`
+    pub fn endpoint(&self) -> String {
+        let mut endpoint = self.url.trim_end_matches('/').to_string();
+        if let Some(base_path) = &self.base_path {
+            endpoint.push('/');
+            endpoint.push_str(base_path.trim_matches('/'));
+        }
+        endpoint.push('/');
+        endpoint
+    }
+
+    /// How long to wait after the failed attempt number `attempt` (from 0)
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        self.connect_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_CONNECT_BACKOFF)
+    }
+
+    /// Sets the capabilities controlled by these options on `caps`
+    pub fn apply<C: AppiumCapability>(&self, caps: &mut C) {
+        if let Some(seconds) = self.new_command_timeout {
+            caps.set_number(NEW_COMMAND_TIMEOUT_CAP, Number::from(seconds));
+        }
+    }
+}
+
+/// Whether a caps file key is handled by [`ServerOptions`] rather than passed through
+pub fn is_server_capability(key: &str) -> bool {
+    key.starts_with(CAPS_PREFIX) || key == NEW_COMMAND_TIMEOUT_CAP
+}
+
+/// String capability the launchers can't do without, looked up under each of `keys` in turn
+pub fn required_capability<'a>(
+    capabilities: &'a HashMap<String, Value>,
+    keys: &[&str],
+) -> Result<&'a str> {
+    let Some((key, value)) = keys
+        .iter()
+        .find_map(|key| capabilities.get(*key).map(|value| (*key, value)))
+    else {
+        return Err(RustPilotError::Config(format!(
+            "Missing required capability {}",
+            keys.join(" or ")
+        )));
+    };
+    value.as_str().ok_or_else(|| {
+        RustPilotError::Config(format!(
+            "Invalid value for key: {} (expected a string, got {})",
+            key, value
+        ))
+    })
+}
+
+/// Copies every caps file entry, whatever its JSON type, into Appium capabilities
+///
+/// `skip` lists keys the launcher sets itself; rust_pilot's own keys are never copied.
+pub fn set_capabilities<C: AppiumCapability>(
+    caps: &mut C,
+    capabilities: &HashMap<String, Value>,
+    skip: &[&str],
+) {
+    for (key, value) in capabilities {
+        if !skip.contains(&key.as_str()) && !is_server_capability(key) {
+            caps.insert(key.clone(), value.clone());
+        }
+    }
+}
+
+/// Opens an Appium session, retrying with backoff while the server can't be reached
+///
+/// Sessions the server refuses (bad capabilities, no device) aren't retried.
+pub async fn connect<C: AppiumCapability>(
+    mut caps: C,
+    server: &ServerOptions,
+) -> Result<Client<C>> {
+    server.apply(&mut caps);
+    let endpoint = server.endpoint();
+    let builder = ClientBuilder::native(caps);
+
+    let mut attempt = 0;
+    loop {
+        Logger::debug(format!(
+            "Creating session on {} (attempt {})",
+            endpoint,
+            attempt + 1
+        ));
+        let reason =
+            match tokio::time::timeout(server.connect_timeout, builder.connect(&endpoint)).await {
+                Ok(Ok(client)) => return Ok(client),
+                Ok(Err(err)) if !is_transient(&err) => {
+                    return Err(RustPilotError::Connection(format!(
+                        "Appium at {} refused the session: {}",
+                        endpoint, err
+                    )))
+                }
+                Ok(Err(err)) => err.to_string(),
+                Err(_) => format!("timed out after {}s", server.connect_timeout.as_secs()),
+            };
+
+        if attempt >= server.connect_retries {
+            return Err(RustPilotError::Connection(format!(
+                "Could not reach Appium at {} after {} attempt(s): {}",
+                endpoint,
+                attempt + 1,
+                reason
+            )));
+        }
+        let delay = server.backoff(attempt);
+        Logger::warning(format!(
+            "Could not reach Appium at {}: {}. Retrying in {:.1}s",
+            endpoint,
+            reason,
+            delay.as_secs_f64()
+        ));
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Ends the session of `client`, freeing the device for the next flow
+///
+/// Best effort: the run already has its result, so a failure is only logged.
+pub async fn disconnect(client: &fantoccini::Client) {
+    if let Err(err) = client.clone().close().await {
+        Logger::warning(format!("Could not close the Appium session: {}", err));
+    }
+}
+
+/// Errors worth retrying: the server is down, restarting or dropped the connection
+fn is_transient(err: &NewSessionError) -> bool {
+    matches!(err, NewSessionError::Failed(_) | NewSessionError::Lost(_))
+}
+
+fn caps_str(capabilities: &HashMap<String, Value>, key: &str) -> Result<Option<String>> {
+    match capabilities.get(key) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(RustPilotError::Config(format!(
+            "Invalid value for key: {} (expected a string)",
+            key
+        ))),
+    }
+}
+
+/// A number of seconds or retries, from the environment or the caps file
+fn setting(
+    env: &impl Fn(&str) -> Option<String>,
+    env_name: &str,
+    capabilities: &HashMap<String, Value>,
+    caps_key: &str,
+) -> Result<Option<u64>> {
+    if let Some(value) = env(env_name) {
+        return value.trim().parse().map(Some).map_err(|_| {
+            RustPilotError::Config(format!(
+                "Invalid value for {}: {} (expected a whole number)",
+                env_name, value
+            ))
+        });
+    }
+    match capabilities.get(caps_key) {
+        None => Ok(None),
+        Some(value) => value.as_u64().map(Some).ok_or_else(|| {
+            RustPilotError::Config(format!(
+                "Invalid value for key: {} (expected a whole number)",
+                caps_key
+            ))
+        }),
+    }
+}
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    use serde_json::{json, Value};
+
+    use appium_client::capabilities::android::AndroidCapabilities;
+
+    use crate::common::session::{
+        is_server_capability, required_capability, set_capabilities, ServerOptions, ServerOverrides,
+    };
+    use crate::error::RustPilotError;
+
+    fn caps(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).expect("caps should be an object")
+    }
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn test_defaults() {
+        let server =
+            ServerOptions::resolve(&ServerOverrides::default(), &HashMap::new(), no_env).unwrap();
+        assert_eq!(server, ServerOptions::default());
+        assert_eq!(server.endpoint(), "http://localhost:4723/");
+    }
+
+    #[test]
+    fn test_cli_wins_over_env_and_env_over_caps() {
+        let capabilities = caps(json!({
+            "rustPilot:appiumUrl": "http://caps:4723",
+            "rustPilot:basePath": "/caps",
+            "rustPilot:connectTimeout": 30,
+            "rustPilot:connectRetries": 5,
+            "appium:newCommandTimeout": 300,
+        }));
+        let env = |name: &str| match name {
+            "RUST_PILOT_APPIUM_URL" => Some("http://env:4723".to_string()),
+            "RUST_PILOT_CONNECT_RETRIES" => Some("1".to_string()),
+            _ => None,
+        };
+        let overrides = ServerOverrides {
+            url: Some("http://cli:4444".to_string()),
+            new_command_timeout: Some(60),
+            ..ServerOverrides::default()
+        };
+
+        let server = ServerOptions::resolve(&overrides, &capabilities, env).unwrap();
+        assert_eq!(server.url, "http://cli:4444");
+        assert_eq!(server.base_path.as_deref(), Some("/caps"));
+        assert_eq!(server.connect_timeout, Duration::from_secs(30));
+        assert_eq!(server.connect_retries, 1);
+        assert_eq!(server.new_command_timeout, Some(60));
+        assert_eq!(server.endpoint(), "http://cli:4444/caps/");
+    }
+
+    #[test]
+    fn test_invalid_settings_are_config_errors() {
+        let bad_env =
+            |name: &str| (name == "RUST_PILOT_CONNECT_TIMEOUT").then(|| "soon".to_string());
+        assert!(matches!(
+            ServerOptions::resolve(&ServerOverrides::default(), &HashMap::new(), bad_env),
+            Err(RustPilotError::Config(message)) if message.contains("RUST_PILOT_CONNECT_TIMEOUT")
+        ));
+
+        let bad_caps = caps(json!({ "rustPilot:connectRetries": "three" }));
+        assert!(matches!(
+            ServerOptions::resolve(&ServerOverrides::default(), &bad_caps, no_env),
+            Err(RustPilotError::Config(message)) if message.contains("rustPilot:connectRetries")
+        ));
+    }
+
+    #[test]
+    fn test_endpoint_joins_base_path() {
+        let server = |url: &str, base_path: Option<&str>| ServerOptions {
+            url: url.to_string(),
+            base_path: base_path.map(str::to_string),
+            ..ServerOptions::default()
+        };
+        assert_eq!(
+            server("http://grid:4444", Some("/wd/hub")).endpoint(),
+            "http://grid:4444/wd/hub/"
+        );
+        assert_eq!(
+            server("http://grid:4444/", Some("wd/hub/")).endpoint(),
+            "http://grid:4444/wd/hub/"
+        );
+        assert_eq!(
+            server("http://grid:4444/wd/hub", None).endpoint(),
+            "http://grid:4444/wd/hub/"
+        );
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_a_cap() {
+        let server = ServerOptions::default();
+        assert_eq!(server.backoff(0), Duration::from_secs(1));
+        assert_eq!(server.backoff(1), Duration::from_secs(2));
+        assert_eq!(server.backoff(3), Duration::from_secs(8));
+        assert_eq!(server.backoff(10), Duration::from_secs(30));
+        assert_eq!(server.backoff(u32::MAX), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_server_capabilities_are_not_forwarded() {
+        assert!(is_server_capability("rustPilot:appiumUrl"));
+        assert!(is_server_capability("appium:newCommandTimeout"));
+        assert!(!is_server_capability("appium:app"));
+    }
+
+    #[test]
+    fn test_required_capability() {
+        let capabilities = caps(json!({
+            "platformVersion": "14",
+            "appium:app": 42,
+        }));
+        assert_eq!(
+            required_capability(
+                &capabilities,
+                &["appium:platformVersion", "platformVersion"]
+            )
+            .unwrap(),
+            "14"
+        );
+        assert!(matches!(
+            required_capability(&capabilities, &["appium:app", "app"]),
+            Err(RustPilotError::Config(message)) if message.contains("appium:app") && message.contains("42")
+        ));
+        assert!(matches!(
+            required_capability(&capabilities, &["appium:udid", "udid"]),
+            Err(RustPilotError::Config(message)) if message == "Missing required capability appium:udid or udid"
+        ));
+    }
+
+    #[test]
+    fn test_set_capabilities_forwards_every_json_type() {
+        let capabilities = caps(json!({
+            "platformVersion": "14",
+            "appium:newCommandTimeout": 300,
+            "appium:otherApps": ["a.apk", "b.apk"],
+            "appium:chromeOptions": { "args": ["--headless"] },
+            "appium:noReset": true,
+            "rustPilot:connectRetries": 1,
+        }));
+        let mut android = AndroidCapabilities::new();
+        set_capabilities(&mut android, &capabilities, &["platformVersion"]);
+
+        assert_eq!(
+            android.get("appium:otherApps"),
+            Some(&json!(["a.apk", "b.apk"]))
+        );
+        assert_eq!(
+            android.get("appium:chromeOptions"),
+            Some(&json!({ "args": ["--headless"] }))
+        );
+        assert_eq!(android.get("appium:noReset"), Some(&json!(true)));
+        assert_eq!(android.get("platformVersion"), None);
+        assert_eq!(android.get("rustPilot:connectRetries"), None);
+        // Set from ServerOptions when the session is created
+        assert_eq!(android.get("appium:newCommandTimeout"), None);
+    }
+}
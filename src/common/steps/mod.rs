@@ -1,135 +1,203 @@
-use std::path::Path;
-use std::process;
-use chrono;
+#[cfg(test)]
+mod test;
+
 use colored::Colorize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
-use crate::common::models::Step;
-use crate::common::utils::{parse_test_file, set_current_indent_level};
+use crate::common::models::{SourceLocation, Step};
+use crate::common::utils::{
+    get_content, parse_test_file_with_positions, step_positions, StepPosition,
+};
+use crate::common::variables::Variables;
+use crate::error::{Result, RustPilotError};
 use crate::logger::Logger;
 
+/// Source of Mermaid node ids, unique for the whole process
+static NEXT_NODE_ID: AtomicUsize = AtomicUsize::new(1);
+
+fn next_node_id() -> usize {
+    NEXT_NODE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Mermaid node for the root of a flow tree, unique even when several flows share a graph
+pub fn root_node(label: &str) -> String {
+    format!("idRoot{}({})", next_node_id(), label)
+}
+
+/// A step of a flattened flow, with the variables of the flow it comes from
+#[derive(Debug, Clone)]
+pub struct FlowStep {
+    pub step: Step,
+    pub variables: Arc<Variables>,
+    /// Where the step was declared; unknown for flow-style step lists
+    pub source: Option<SourceLocation>,
+    /// `appId` of the flow header, inherited from the including flow when unset
+    pub app_id: Option<String>,
+}
+
 /// Flattens a list of steps, resolving any `RunFlow` steps recursively.
+///
+/// `flow_file` is the file the steps were read from; `variables` and `app_id` are
+/// the ones of the top-level flow, included flows get their own.
 pub async fn flatten_steps(
     steps: Vec<Step>,
-    base_path: &Path,
+    flow_file: &Path,
     mermaid_parent_id: String,
-) -> (Vec<Step>, String) {
-    flatten_steps_with_indent(steps, base_path, mermaid_parent_id, 0).await
+    variables: Variables,
+    app_id: Option<String>,
+) -> Result<(Vec<FlowStep>, String)> {
+    let positions = get_content(flow_file)
+        .map(|content| step_positions(&content))
+        .unwrap_or_default();
+    flatten_steps_with_indent(
+        steps,
+        &positions,
+        &[flow_file.to_path_buf()],
+        mermaid_parent_id,
+        Arc::new(variables),
+        app_id.as_deref(),
+        0,
+    )
+    .await
 }
 
 /// Internal implementation of flatten_steps that tracks the indentation level
+///
+/// `chain` holds the files from the top-level flow down to the one of `steps`.
 async fn flatten_steps_with_indent(
     steps: Vec<Step>,
-    base_path: &Path,
+    positions: &[StepPosition],
+    chain: &[PathBuf],
     mermaid_parent_id: String,
+    variables: Arc<Variables>,
+    app_id: Option<&str>,
     indent_level: usize,
-) -> (Vec<Step>, String) {
-    let mut flattened_steps: Vec<Step> = Vec::new();
+) -> Result<(Vec<FlowStep>, String)> {
+    let mut flattened_steps: Vec<FlowStep> = Vec::new();
     let mut mermaid_steps = String::new();
+    let flow_file = chain
+        .last()
+        .expect("the include chain starts with the top-level flow");
+    let base_path = flow_file.parent().unwrap_or(Path::new(""));
 
-    for step in steps {
+    for (index, step) in steps.into_iter().enumerate() {
         match step {
             Step::RunFlow { runFlow } => {
-                let now = chrono::Local::now().timestamp_millis();
-                let id = format!("idRunFlow{}({})", now, runFlow);
-                mermaid_steps.push_str(&format!("{} --> {}\\n", mermaid_parent_id, id));
-                let step_path = base_path.join(&runFlow);
+                let line = positions.get(index).map(|(line, _)| *line);
+                let flow_name = variables.interpolate(runFlow.file())?;
+                let id = format!("idRunFlow{}({})", next_node_id(), flow_name);
+                mermaid_steps.push_str(&format!("{} --> {}\n", mermaid_parent_id, id));
+                let step_path = base_path.join(&flow_name);
                 let string_path = step_path.display().to_string();
-                
+
                 // Use indented logging
-                Logger::info_with_indent(format!("Loading step file {}", string_path.blue()), indent_level);
+                Logger::info_with_indent(
+                    format!("Loading step file {}", string_path.blue()),
+                    indent_level,
+                );
 
                 // Verify file existence
                 if !step_path.exists() {
-                    Logger::error_with_indent(
-                        format!("Error: File {} does not exist", step_path.display()),
-                        indent_level
-                    );
-                    process::exit(1);
+                    let mut message = format!("runFlow file {} does not exist", string_path);
+                    if chain.len() > 1 {
+                        message.push_str(&format!(" (include chain: {})", describe_chain(chain)));
+                    }
+                    return Err(RustPilotError::flow_resolution_at(flow_file, line, message));
+                }
+
+                // A flow including itself, directly or not, would never end
+                let mut step_chain = chain.to_vec();
+                step_chain.push(step_path.clone());
+                if includes_flow(chain, &step_path) {
+                    return Err(RustPilotError::flow_resolution_at(
+                        flow_file,
+                        line,
+                        format!("runFlow cycle: {}", describe_chain(&step_chain)),
+                    ));
                 }
 
                 // Parse the step file
-                let (_, steps) = parse_test_file(&step_path);
+                let (header, steps, step_positions) = parse_test_file_with_positions(&step_path)?;
+                let flow_variables = variables
+                    .with_overrides(runFlow.env())
+                    .with_defaults(header.env.as_ref());
 
                 // Store the current indentation level for the nested steps
                 let next_indent_level = indent_level + 1;
-                
+
                 // Recursively flatten the steps from the loaded file with one more level of indentation
                 // Use Box::pin to avoid infinitely sized future with recursive async calls
-                let (sub_steps, mermaid_sub_steps) =
-                    Box::pin(flatten_steps_with_indent(
-                        steps, 
-                        step_path.parent().unwrap(), 
-                        id,
-                        next_indent_level // Increase indent level for nested steps
-                    )).await;
-                
-                // Add the indentation level to each step
-                let mut indented_steps: Vec<Step> = Vec::new();
-                for step in sub_steps {
-                    // Store the indentation level in the step metadata
-                    let step_with_indent = match step {
-                        Step::RunFlow { runFlow } => Step::RunFlow { runFlow },
-                        Step::TapOn { tapOn } => {
-                            // Set the indentation level for this step
-                            set_current_indent_level(next_indent_level);
-                            Step::TapOn { tapOn }
-                        },
-                        Step::RunScript { runScript } => {
-                            set_current_indent_level(next_indent_level);
-                            Step::RunScript { runScript }
-                        },
-                        Step::InputText { inputText } => {
-                            set_current_indent_level(next_indent_level);
-                            Step::InputText { inputText }
-                        },
-                        Step::AssertVisible { assertVisible } => {
-                            set_current_indent_level(next_indent_level);
-                            Step::AssertVisible { assertVisible }
-                        },
-                        Step::AssertNotVisible { assertNotVisible } => {
-                            set_current_indent_level(next_indent_level);
-                            Step::AssertNotVisible { assertNotVisible }
-                        },
-                        Step::LaunchApp { launchApp } => {
-                            set_current_indent_level(next_indent_level);
-                            Step::LaunchApp { launchApp }
-                        },
-                        Step::Swipe { swipe } => {
-                            set_current_indent_level(next_indent_level);
-                            Step::Swipe { swipe }
-                        },
-                    };
-                    indented_steps.push(step_with_indent);
-                }
-                
-                flattened_steps.extend(indented_steps);
+                let (sub_steps, mermaid_sub_steps) = Box::pin(flatten_steps_with_indent(
+                    steps,
+                    &step_positions,
+                    &step_chain,
+                    id,
+                    Arc::new(flow_variables),
+                    header.appId.as_deref().or(app_id),
+                    next_indent_level, // Increase indent level for nested steps
+                ))
+                .await?;
+
+                // Each step keeps its depth in its source location
+                flattened_steps.extend(sub_steps);
 
                 let string_path = step_path.display().to_string();
                 Logger::success_with_indent(
                     format!("Steps from {} loaded successfully", string_path.blue()),
-                    indent_level
+                    indent_level,
                 );
                 mermaid_steps.push_str(&mermaid_sub_steps);
             }
             step => {
-                let now = chrono::Local::now().timestamp_millis();
                 let step_name = format!("{:?}", step);
-                let step_name: String = step_name.split_whitespace().next().unwrap().to_string();
-                let node = format!("idStepName{}({})", now, step_name);
-                mermaid_steps.push_str(&format!("{} --> {}\\n", mermaid_parent_id, node));
-                
-                // For top-level steps (indent_level == 0), we need to set the indentation level to 0
-                // For nested steps, the indentation level is already set in the RunFlow branch
-                if indent_level == 0 {
-                    set_current_indent_level(0);
-                } else {
-                    set_current_indent_level(indent_level);
-                }
-                
-                flattened_steps.push(step);
+                let step_name: String = step_name
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default()
+                    .to_string();
+                let node = format!("idStepName{}({})", next_node_id(), step_name);
+                mermaid_steps.push_str(&format!("{} --> {}\n", mermaid_parent_id, node));
+
+                let source = positions.get(index).map(|&(line, column)| SourceLocation {
+                    file: flow_file.display().to_string(),
+                    line,
+                    column,
+                    include_chain: chain
+                        .iter()
+                        .map(|file| file.display().to_string())
+                        .collect(),
+                    depth: indent_level,
+                });
+                flattened_steps.push(FlowStep {
+                    step,
+                    variables: variables.clone(),
+                    source,
+                    app_id: app_id.map(str::to_string),
+                });
             }
         }
     }
 
-    (flattened_steps, mermaid_steps)
+    Ok((flattened_steps, mermaid_steps))
+}
+
+/// Whether `path` is one of the files of `chain`
+fn includes_flow(chain: &[PathBuf], path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    chain
+        .iter()
+        .any(|file| file.canonicalize().is_ok_and(|file| file == path))
+}
+
+/// `main.yml -> login.yml -> ...`
+fn describe_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|file| file.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
 }
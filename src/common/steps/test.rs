@@ -0,0 +1,198 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use crate::common::models::SourceLocation;
+    use crate::common::steps::flatten_steps;
+    use crate::common::utils::parse_test_file;
+    use crate::common::variables::Variables;
+    use crate::error::RustPilotError;
+
+    fn write(dir: &Path, name: &str, contents: &str) {
+        fs::write(dir.join(name), contents).expect("Failed to write flow");
+    }
+
+    async fn flatten(dir: &Path, file: &str) -> Result<usize, RustPilotError> {
+        let path = dir.join(file);
+        let (_, steps) = parse_test_file(&path)?;
+        flatten_steps(
+            steps,
+            &path,
+            "idRoot0(test)".to_string(),
+            Variables::default(),
+            None,
+        )
+        .await
+        .map(|(steps, _)| steps.len())
+    }
+
+    #[tokio::test]
+    async fn test_run_flow_cycle_shows_include_chain() {
+        let dir = std::env::temp_dir().join("rust_pilot_run_flow_cycle");
+        fs::create_dir_all(&dir).expect("Failed to create test directory");
+        write(
+            &dir,
+            "main.yml",
+            "appId: app\n---\n- tapOn: A\n- runFlow: login.yml\n",
+        );
+        write(
+            &dir,
+            "login.yml",
+            "appId: app\n---\n- tapOn: B\n\n- runFlow:\n    file: main.yml\n",
+        );
+        write(
+            &dir,
+            "itself.yml",
+            "appId: app\n---\n- runFlow: ./itself.yml\n",
+        );
+
+        let cycle = flatten(&dir, "main.yml").await;
+        let itself = flatten(&dir, "itself.yml").await;
+        fs::remove_dir_all(&dir).expect("Failed to clean up test directory");
+
+        let message = cycle.unwrap_err().to_string();
+        assert!(
+            message.contains(&format!("{}:5:", dir.join("login.yml").display())),
+            "{}",
+            message
+        );
+        assert!(message.contains(&format!(
+            "runFlow cycle: {} -> {} -> {}",
+            dir.join("main.yml").display(),
+            dir.join("login.yml").display(),
+            dir.join("main.yml").display()
+        )));
+        assert!(matches!(
+            itself,
+            Err(RustPilotError::FlowResolution { line: Some(3), .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_missing_run_flow_names_file_and_line() {
+        let dir = std::env::temp_dir().join("rust_pilot_run_flow_missing");
+        fs::create_dir_all(&dir).expect("Failed to create test directory");
+        write(&dir, "main.yml", "appId: app\n---\n- runFlow: login.yml\n");
+        write(
+            &dir,
+            "login.yml",
+            "appId: app\n---\n- tapOn: A\n- runFlow: otp.yml\n",
+        );
+        write(&dir, "shared.yml", "appId: app\n---\n- tapOn: A\n");
+        write(
+            &dir,
+            "twice.yml",
+            "appId: app\n---\n- runFlow: shared.yml\n- runFlow: shared.yml\n",
+        );
+
+        let missing = flatten(&dir, "main.yml").await;
+        // Including the same flow twice is not a cycle
+        let twice = flatten(&dir, "twice.yml").await;
+        fs::remove_dir_all(&dir).expect("Failed to clean up test directory");
+
+        let err = missing.unwrap_err();
+        assert!(matches!(
+            &err,
+            RustPilotError::FlowResolution { path, line: Some(4), .. } if path == &dir.join("login.yml")
+        ));
+        assert!(err.to_string().contains(&format!(
+            "include chain: {} -> {}",
+            dir.join("main.yml").display(),
+            dir.join("login.yml").display()
+        )));
+        assert_eq!(twice.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_flattened_steps_know_where_they_come_from() {
+        let dir = std::env::temp_dir().join("rust_pilot_step_sources");
+        fs::create_dir_all(dir.join("login")).expect("Failed to create test directory");
+        write(
+            &dir,
+            "main.yml",
+            "appId: app\n---\n- tapOn: A\n- runFlow: login/login.yml\n",
+        );
+        write(
+            &dir,
+            "login/login.yml",
+            "appId: app\n---\n# email\n  - inputText: a@b.co\n",
+        );
+
+        let path = dir.join("main.yml");
+        let (_, steps) = parse_test_file(&path).unwrap();
+        let flattened = flatten_steps(
+            steps,
+            &path,
+            "idRoot0(test)".to_string(),
+            Variables::default(),
+            None,
+        )
+        .await;
+        fs::remove_dir_all(&dir).expect("Failed to clean up test directory");
+
+        let sources: Vec<SourceLocation> = flattened
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|flow_step| flow_step.source.expect("block steps have a source"))
+            .collect();
+        let main = path.display().to_string();
+        let login = dir.join("login").join("login.yml").display().to_string();
+        assert_eq!(
+            sources[0],
+            SourceLocation {
+                file: main.clone(),
+                line: 3,
+                column: 1,
+                include_chain: vec![main.clone()],
+                depth: 0,
+            }
+        );
+        assert_eq!(
+            (sources[1].line, sources[1].column, sources[1].depth),
+            (4, 3, 1)
+        );
+        assert_eq!(sources[1].include_chain, vec![main.clone(), login.clone()]);
+        assert_eq!(
+            sources[1].to_string(),
+            format!("{}:4:3 (via {} -> {})", login, main, login)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_included_flows_inherit_or_override_app_id() {
+        let dir = std::env::temp_dir().join("rust_pilot_step_app_ids");
+        fs::create_dir_all(&dir).expect("Failed to create test directory");
+        write(
+            &dir,
+            "main.yml",
+            "appId: com.main\n---\n- runFlow: same.yml\n- runFlow: other.yml\n",
+        );
+        write(&dir, "same.yml", "tags: [shared]\n---\n- tapOn: A\n");
+        write(&dir, "other.yml", "appId: com.other\n---\n- tapOn: B\n");
+
+        let path = dir.join("main.yml");
+        let (header, steps) = parse_test_file(&path).unwrap();
+        let flattened = flatten_steps(
+            steps,
+            &path,
+            "idRoot0(test)".to_string(),
+            Variables::default(),
+            header.appId,
+        )
+        .await;
+        fs::remove_dir_all(&dir).expect("Failed to clean up test directory");
+
+        let app_ids: Vec<Option<String>> = flattened
+            .unwrap()
+            .0
+            .into_iter()
+            .map(|flow_step| flow_step.app_id)
+            .collect();
+        assert_eq!(
+            app_ids,
+            vec![Some("com.main".to_string()), Some("com.other".to_string())]
+        );
+    }
+}
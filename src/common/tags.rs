@@ -22,3 +22,6 @@ pub fn valid_report_tag() -> String {
 pub fn invalid_report_tag() -> String {
     "❌ Error".to_string()
 }
+pub fn skipped_report_tag() -> String {
+    "⏭️ Skipped".to_string()
+}
@@ -3,168 +3,209 @@
 #[cfg(test)]
 mod test;
 
-use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::Path;
-use std::process;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 // Removed unused import: colored::Colorize
 
-use fantoccini::Client as FantoClient;
+use chrono::Local;
 use serde::de::DeserializeOwned;
+use serde_json::Value;
 use serde_yaml;
 use serde_yaml::Deserializer;
 use tokio::time::sleep;
 
+use crate::common::context::ExecutionContext;
+use crate::common::driver::Driver;
 use crate::common::models::{Step, TestFileHeader};
-use crate::logger::Logger;
+use crate::error::{Result, RustPilotError};
 
-// Thread-local storage for indentation level
-thread_local! {
-    static CURRENT_INDENT_LEVEL: RefCell<usize> = RefCell::new(0);
-}
-
-/// Get the current indentation level
-pub fn get_current_indent_level() -> usize {
-    CURRENT_INDENT_LEVEL.with(|level| *level.borrow())
-}
-
-/// Set the current indentation level
-pub fn set_current_indent_level(level: usize) {
-    CURRENT_INDENT_LEVEL.with(|current_level| *current_level.borrow_mut() = level);
+/// Pause execution for the specified duration
+pub async fn pause_action(context: &ExecutionContext, duration: u64) {
+    context.info(format!("Pausing for {} ms", duration));
+    sleep(Duration::from_millis(duration)).await;
+    context.success("Pause completed");
 }
 
-/// Plain text logger for step execution
-pub struct PlainLogger {
-    pub message: String,
-    pub indent_level: usize,
+/// Take a screenshot on error, returning its path relative to `dir` when it was saved
+///
+/// Best effort: a failing capture is logged but never hides the original error.
+pub async fn error_take_screenshot<D: Driver + ?Sized>(
+    driver: &D,
+    context: &ExecutionContext,
+    dir: &Path,
+    path: &Path,
+) -> Option<String> {
+    context.info("Taking error screenshot");
+    save_screenshot(driver, context, dir, path).await
 }
 
-/// Create a spinner with a message
-pub fn start_spinner(message: String) -> PlainLogger {
-    let indent_level = get_current_indent_level();
-    if indent_level > 0 {
-        Logger::step_with_indent(message.clone(), indent_level);
-    } else {
-        Logger::step(message.clone());
-    }
-    PlainLogger {
-        message,
-        indent_level,
-    }
-}
-
-/// Stop a spinner with completion message
-pub fn stop_spinner(logger: &mut PlainLogger) {
-    let message = format!("{} - Completed", logger.message);
-    if logger.indent_level > 0 {
-        Logger::success_with_indent(message, logger.indent_level);
-    } else {
-        Logger::success(message);
+/// Take a screenshot to `path` inside `dir`, returning `path` when it was saved
+///
+/// Best effort: a failing capture is logged and the step keeps its outcome.
+pub async fn save_screenshot<D: Driver + ?Sized>(
+    driver: &D,
+    context: &ExecutionContext,
+    dir: &Path,
+    path: &Path,
+) -> Option<String> {
+    let file = dir.join(path).display().to_string();
+    match take_screenshot(driver, context, &file).await {
+        Ok(()) => Some(path.display().to_string()),
+        Err(err) => {
+            context.error(format!("Couldn't take screenshot: {}", err));
+            None
+        }
     }
 }
 
-impl PlainLogger {
-    /// Stop with a custom message
-    pub fn stop_with_symbol(&self, message: &str) {
-        if self.indent_level > 0 {
-            Logger::info_with_indent(message, self.indent_level);
-        } else {
-            Logger::info(message);
-        }
-    }
+/// File the screenshot of the step at `index` of `flow` is written to, inside `dir`
+///
+/// Named after the flow, the step and the time, so no run overwrites another's.
+pub fn screenshot_path(dir: &Path, flow: Option<&str>, index: usize) -> PathBuf {
+    let flow = flow
+        .and_then(|flow| Path::new(flow).file_stem())
+        .map(|stem| {
+            stem.to_string_lossy()
+                .replace(|c: char| !c.is_alphanumeric() && c != '-', "_")
+        })
+        .unwrap_or_else(|| "flow".to_string());
+    dir.join(format!(
+        "{}_{:03}_{}.png",
+        flow,
+        index + 1,
+        Local::now().format("%Y%m%d_%H-%M-%S%.3f")
+    ))
 }
 
-/// Pause execution for the specified duration
-pub async fn pause_action(duration: u64) {
-    let indent_level = get_current_indent_level();
-    if indent_level > 0 {
-        Logger::info_with_indent(format!("Pausing for {} ms", duration), indent_level);
-    } else {
-        Logger::info(format!("Pausing for {} ms", duration));
+/// Take a screenshot with the specified filename, creating its directory if needed
+pub async fn take_screenshot<D: Driver + ?Sized>(
+    driver: &D,
+    context: &ExecutionContext,
+    take_screenshot: &str,
+) -> Result<()> {
+    context.start_spinner(format!("Taking screenshot: {}", take_screenshot));
+
+    let screenshot = driver.screenshot().await?;
+    if let Some(dir) = Path::new(take_screenshot).parent() {
+        fs::create_dir_all(dir).map_err(|e| RustPilotError::io(dir, e))?;
     }
+    let mut file =
+        File::create(take_screenshot).map_err(|e| RustPilotError::io(take_screenshot, e))?;
+    file.write_all(&screenshot)
+        .map_err(|e| RustPilotError::io(take_screenshot, e))?;
 
-    sleep(Duration::from_millis(duration)).await;
-
-    if indent_level > 0 {
-        Logger::success_with_indent("Pause completed", indent_level);
-    } else {
-        Logger::success("Pause completed");
-    }
+    context.success("Screenshot taken");
+    Ok(())
 }
 
-/// Take a screenshot on error
-pub async fn error_take_screenshot(client: &FantoClient) {
-    // Set indentation level to 0 for error screenshots
-    set_current_indent_level(0);
-    Logger::info("Taking error screenshot");
-    take_screenshot(client, "error_screenshot.png").await;
+/// Parse a test file and return its header and steps
+pub fn parse_test_file<P: AsRef<Path>>(path: P) -> Result<(TestFileHeader, Vec<Step>)> {
+    parse_test_file_with_positions(path).map(|(header, steps, _)| (header, steps))
 }
 
-/// Take a screenshot with the specified filename
-pub async fn take_screenshot(client: &FantoClient, take_screenshot: &str) {
-    // Use the Logger for consistent formatting
-    let indent_level = get_current_indent_level();
-    Logger::step_with_indent(
-        format!("Taking screenshot: {}", take_screenshot),
-        indent_level,
-    );
-
-    let screenshot = client.screenshot().await.unwrap();
-    let mut file = File::create(&take_screenshot).unwrap();
-    file.write_all(&screenshot).unwrap();
-
-    Logger::success_with_indent("Screenshot taken", indent_level);
+/// Like [`parse_test_file`], also returning the position of each step
+pub fn parse_test_file_with_positions<P: AsRef<Path>>(
+    path: P,
+) -> Result<(TestFileHeader, Vec<Step>, Vec<StepPosition>)> {
+    let content = get_content(&path)?;
+    let (header, steps) = deserialize_test_file(&content).map_err(|err| match err {
+        RustPilotError::Parse { context, message } => RustPilotError::parse(
+            format!("{} of {}", context, path.as_ref().display()),
+            message,
+        ),
+        err => err,
+    })?;
+    Ok((header, steps, step_positions(&content)))
 }
 
-/// Parse a test file and return its header and steps
-pub fn parse_test_file<P: AsRef<Path>>(path: P) -> (TestFileHeader, Vec<Step>) {
-    let content = get_content(&path);
-    let (header, steps) = deserialize_test_file(&content);
-    (header, steps)
+/// Device the caps file targets, as recorded in run results
+pub fn device_name(capabilities: &HashMap<String, Value>) -> Option<String> {
+    ["appium:deviceName", "deviceName", "appium:udid", "udid"]
+        .iter()
+        .find_map(|key| capabilities.get(*key).and_then(|value| value.as_str()))
+        .map(str::to_string)
 }
 
 /// Read content from a file
-pub fn get_content<P: AsRef<Path>>(path: P) -> String {
-    match fs::read_to_string(path.as_ref()) {
-        Ok(content) => content,
-        Err(e) => {
-            Logger::error(format!(
-                "Error reading file {}: {}",
-                path.as_ref().display(),
-                e
-            ));
-            process::exit(1);
-        }
-    }
+pub fn get_content<P: AsRef<Path>>(path: P) -> Result<String> {
+    fs::read_to_string(path.as_ref()).map_err(|e| RustPilotError::io(path.as_ref(), e))
 }
 
 /// Deserialize a test file from YAML content
-pub fn deserialize_test_file(content: &str) -> (TestFileHeader, Vec<Step>) {
+///
+/// A step written as a bare command, like `- launchApp`, reads as `- launchApp:`.
+pub fn deserialize_test_file(content: &str) -> Result<(TestFileHeader, Vec<Step>)> {
     let mut deserializer = Deserializer::from_str(content);
-    let header: TestFileHeader = deserialize_document(deserializer.next(), "header");
-    let steps: Vec<Step> = deserialize_document(deserializer.next(), "steps");
-    (header, steps)
+    let header: TestFileHeader = deserialize_document(deserializer.next(), "header")?;
+    let steps: Vec<serde_yaml::Value> = deserialize_document(deserializer.next(), "steps")?;
+    let steps = steps
+        .into_iter()
+        .map(|step| {
+            let step = match step {
+                serde_yaml::Value::String(command) => serde_yaml::Value::Mapping(
+                    [(command.into(), serde_yaml::Value::Null)]
+                        .into_iter()
+                        .collect(),
+                ),
+                step => step,
+            };
+            serde_yaml::from_value(step).map_err(|e| RustPilotError::parse("steps", e))
+        })
+        .collect::<Result<Vec<Step>>>()?;
+    Ok((header, steps))
+}
+
+/// Line and column of a step in its flow file, both 1-based
+pub type StepPosition = (usize, usize);
+
+/// Position of each step of a test file, in order
+///
+/// Steps are the items of the block sequence in the second YAML document; a
+/// flow-style sequence yields no positions.
+pub fn step_positions(content: &str) -> Vec<StepPosition> {
+    let mut positions = Vec::new();
+    let mut document = 0;
+    let mut document_has_content = false;
+    let mut indent = None;
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if line.starts_with("---") {
+            // A marker before any content starts the first document instead of ending it
+            if document_has_content {
+                document += 1;
+            }
+            document_has_content = false;
+            continue;
+        }
+        document_has_content = true;
+        if document != 1 || !(trimmed == "-" || trimmed.starts_with("- ")) {
+            continue;
+        }
+        let item_indent = line.len() - trimmed.len();
+        if *indent.get_or_insert(item_indent) == item_indent {
+            positions.push((index + 1, item_indent + 1));
+        }
+    }
+    positions
 }
 
 /// Deserialize a document from YAML
 pub fn deserialize_document<T: DeserializeOwned>(
     deserializer: Option<Deserializer>,
     context: &str,
-) -> T {
+) -> Result<T> {
     match deserializer {
-        Some(d) => match T::deserialize(d) {
-            Ok(doc) => doc,
-            Err(e) => {
-                Logger::error(format!("Error deserializing {}: {}", context, e));
-                process::exit(1);
-            }
-        },
-        None => {
-            Logger::error(format!("Missing {} in YAML file", context));
-            process::exit(1);
-        }
+        Some(d) => T::deserialize(d).map_err(|e| RustPilotError::parse(context, e)),
+        None => Err(RustPilotError::parse(
+            context,
+            "missing document in YAML file",
+        )),
     }
 }
@@ -1,34 +1,60 @@
 #[cfg(test)]
 mod tests {
-    use crate::common::utils::{get_current_indent_level, set_current_indent_level, PlainLogger};
+    use std::path::Path;
+
+    use crate::common::utils::{
+        deserialize_test_file, get_content, screenshot_path, step_positions,
+    };
+    use crate::error::RustPilotError;
 
     #[test]
-    fn test_indent_level() {
-        // Set the indent level
-        set_current_indent_level(3);
-        
-        // Check if the indent level is correctly set
-        assert_eq!(get_current_indent_level(), 3);
-        
-        // Change the indent level
-        set_current_indent_level(5);
-        
-        // Check if the indent level is correctly updated
-        assert_eq!(get_current_indent_level(), 5);
-        
-        // Reset the indent level for other tests
-        set_current_indent_level(0);
+    fn test_get_content_missing_file_is_io_error() {
+        let result = get_content("non_existent_flow.yml");
+        assert!(matches!(result, Err(RustPilotError::Io { .. })));
     }
 
     #[test]
-    fn test_plain_logger_creation() {
-        let message = "Test message".to_string();
-        let logger = PlainLogger {
-            message: message.clone(),
-            indent_level: 0,
-        };
-        
-        assert_eq!(logger.message, message);
-        assert_eq!(logger.indent_level, 0);
+    fn test_deserialize_test_file() {
+        let content = "appId: org.wikipedia\ntags:\n  - android\n---\n- tapOn: \"CONTINUE\"\n- assertVisible: \"Explore\"\n";
+        let (header, steps) = deserialize_test_file(content).expect("Failed to parse test file");
+
+        assert_eq!(header.appId.as_deref(), Some("org.wikipedia"));
+        assert_eq!(steps.len(), 2);
+    }
+
+    #[test]
+    fn test_deserialize_test_file_missing_steps_is_parse_error() {
+        let content = "appId: org.wikipedia\n";
+        let result = deserialize_test_file(content);
+        assert!(matches!(result, Err(RustPilotError::Parse { .. })));
+    }
+
+    #[test]
+    fn test_step_positions() {
+        let content = "---\nappId: app\n---\n# login\n- tapOn:\n    id: email\n- launchApp:\n    permissions:\n    - all\n\n-   inputText: \"- not a step\"\n";
+        assert_eq!(step_positions(content), vec![(5, 1), (7, 1), (11, 1)]);
+
+        let nested = "appId: app\n---\n  - tapOn: A\n  - assertVisible:\n      - B\n";
+        assert_eq!(step_positions(nested), vec![(3, 3), (4, 3)]);
+        assert!(step_positions("appId: app\n---\n[{tapOn: A}]\n").is_empty());
+    }
+
+    #[test]
+    fn test_screenshot_path_is_unique_per_flow_and_step() {
+        let dir = Path::new("reports/screenshots");
+        let login = screenshot_path(dir, Some("flows/log in.yml"), 1);
+        assert_eq!(login.parent(), Some(dir));
+        let name = login.file_name().unwrap().to_string_lossy();
+        assert!(name.starts_with("log_in_002_"), "{}", name);
+        assert!(name.ends_with(".png"));
+
+        let search = screenshot_path(dir, Some("flows/search.yml"), 1);
+        assert_ne!(login.file_name(), search.file_name());
+        let unknown = screenshot_path(dir, None, 0);
+        assert!(unknown
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .starts_with("flow_001_"));
     }
 }
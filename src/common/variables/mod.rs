@@ -0,0 +1,114 @@
+//! Flow variables and `${NAME}` interpolation
+//!
+//! A flow sees the `-e` values and the `env:` of the `runFlow` that included it
+//! first, then the `env:` defaults of its own header and of the flows above it,
+//! then the process environment.
+
+#[cfg(test)]
+mod test;
+
+use serde_json::Value;
+
+use crate::common::models::{Env, Step};
+use crate::error::{Result, RustPilotError};
+use crate::secrets;
+
+/// Variables visible to the steps of one flow
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Variables {
+    /// Set with `-e` or by a `runFlow` env
+    values: Env,
+    /// From `env:` headers, the innermost flow's winning
+    defaults: Env,
+}
+
+impl Variables {
+    /// These variables with `env` set over them, as `-e` and `runFlow` envs are
+    pub fn with_overrides(&self, env: Option<&Env>) -> Self {
+        let mut variables = self.clone();
+        if let Some(env) = env {
+            variables.values.extend(env.clone());
+        }
+        variables
+    }
+
+    /// These variables with the `env:` header of an included flow as defaults
+    pub fn with_defaults(&self, env: Option<&Env>) -> Self {
+        let mut variables = self.clone();
+        if let Some(env) = env {
+            variables.defaults.extend(env.clone());
+        }
+        variables
+    }
+
+    /// Value of `name`, falling back to the process environment
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.values
+            .get(name)
+            .or_else(|| self.defaults.get(name))
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+    }
+
+    /// Replaces every `${NAME}` in `text`; an unknown name is an error
+    pub fn interpolate(&self, text: &str) -> Result<String> {
+        let mut interpolated = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            let Some(length) = rest[start + 2..].find('}') else {
+                break;
+            };
+            let name = &rest[start + 2..start + 2 + length];
+            let value = self.get(name.trim()).ok_or_else(|| {
+                RustPilotError::Config(format!("Undefined variable {} in {:?}", name, text))
+            })?;
+            if secrets::is_secret_name(name.trim()) {
+                secrets::register_value(&value);
+            }
+            interpolated.push_str(&rest[..start]);
+            interpolated.push_str(&value);
+            rest = &rest[start + 3 + length..];
+        }
+        interpolated.push_str(rest);
+        Ok(interpolated)
+    }
+
+    /// `step` with every string field interpolated
+    pub fn interpolate_step(&self, step: &Step) -> Result<Step> {
+        let mut value =
+            serde_json::to_value(step).map_err(|e| RustPilotError::parse(step.kind(), e))?;
+        self.interpolate_value(&mut value)?;
+        serde_json::from_value(value).map_err(|e| RustPilotError::parse(step.kind(), e))
+    }
+
+    fn interpolate_value(&self, value: &mut Value) -> Result<()> {
+        match value {
+            Value::String(text) if text.contains("${") => *text = self.interpolate(text)?,
+            Value::Array(values) => {
+                for value in values {
+                    self.interpolate_value(value)?;
+                }
+            }
+            Value::Object(map) => {
+                for value in map.values_mut() {
+                    self.interpolate_value(value)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Parses a `-e KEY=VALUE` argument
+pub fn parse_assignment(assignment: &str) -> Result<(String, String)> {
+    match assignment.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(RustPilotError::Config(format!(
+            "Invalid variable {:?}, expected KEY=VALUE",
+            assignment
+        ))),
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::common::models::{ElementSelector, Env, Step};
+    use crate::common::steps::flatten_steps;
+    use crate::common::utils::deserialize_test_file;
+    use crate::common::variables::{parse_assignment, Variables};
+    use crate::error::RustPilotError;
+    use crate::secrets;
+
+    fn env(pairs: &[(&str, &str)]) -> Env {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let variables = Variables::default().with_overrides(Some(&env(&[("USER", "ana")])));
+        assert_eq!(
+            variables.interpolate("Hello ${USER}, ${ USER }!").unwrap(),
+            "Hello ana, ana!"
+        );
+        assert_eq!(variables.interpolate("no ${ending").unwrap(), "no ${ending");
+        assert_eq!(variables.interpolate("costs $5").unwrap(), "costs $5");
+        assert!(matches!(
+            variables.interpolate("${RUST_PILOT_SURELY_UNDEFINED}"),
+            Err(RustPilotError::Config(message)) if message.contains("RUST_PILOT_SURELY_UNDEFINED")
+        ));
+    }
+
+    #[test]
+    fn test_precedence() {
+        let variables = Variables::default()
+            .with_defaults(Some(&env(&[("USER", "header"), ("PATH", "header")])))
+            .with_overrides(Some(&env(&[("USER", "cli")])));
+        assert_eq!(variables.get("USER").as_deref(), Some("cli"));
+        // Header defaults win over the process environment
+        assert_eq!(variables.get("PATH").as_deref(), Some("header"));
+
+        let nested = variables.with_defaults(Some(&env(&[("USER", "nested"), ("PIN", "1234")])));
+        assert_eq!(nested.get("USER").as_deref(), Some("cli"));
+        assert_eq!(nested.get("PIN").as_deref(), Some("1234"));
+
+        let process_path = std::env::var("PATH").ok();
+        assert_eq!(Variables::default().get("PATH"), process_path);
+    }
+
+    #[test]
+    fn test_interpolate_step() {
+        let variables = Variables::default()
+            .with_overrides(Some(&env(&[("EMAIL", "a@b.co"), ("ID", "login")])));
+        let (_, steps) = deserialize_test_file(
+            "appId: app\n---\n- inputText: \"${EMAIL}\"\n- tapOn:\n    id: \"${ID}_button\"\n",
+        )
+        .unwrap();
+
+        let Step::InputText { inputText } = variables.interpolate_step(&steps[0]).unwrap() else {
+            panic!("expected an inputText step");
+        };
+        assert_eq!(inputText.text(), "a@b.co");
+        let Step::TapOn {
+            tapOn: ElementSelector::Options(options),
+        } = variables.interpolate_step(&steps[1]).unwrap()
+        else {
+            panic!("expected a tapOn step with options");
+        };
+        assert_eq!(options.id.as_deref(), Some("login_button"));
+    }
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            parse_assignment("PASSWORD=a=b").unwrap(),
+            ("PASSWORD".to_string(), "a=b".to_string())
+        );
+        assert!(parse_assignment("PASSWORD").is_err());
+        assert!(parse_assignment("=value").is_err());
+    }
+
+    #[test]
+    fn test_secret_variables_are_masked_once_interpolated() {
+        let variables = Variables::default().with_overrides(Some(&env(&[
+            ("SECRET_PIN", "pin-7309"),
+            ("API_TOKEN", "token-5521"),
+            ("USER", "visible-ana"),
+        ])));
+        secrets::register_name("API_TOKEN");
+        variables
+            .interpolate("${SECRET_PIN} ${API_TOKEN} ${USER}")
+            .unwrap();
+        assert_eq!(
+            secrets::mask("pin-7309 token-5521 visible-ana"),
+            "*** *** visible-ana"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_flow_env_is_scoped_to_the_included_flow() {
+        let dir = std::env::temp_dir().join("rust_pilot_run_flow_env");
+        fs::create_dir_all(&dir).expect("Failed to create test directory");
+        fs::write(
+            dir.join("login.yml"),
+            "appId: app\nenv:\n  USER: guest\n  PIN: 1234\n---\n- inputText: \"${USER} ${PIN}\"\n",
+        )
+        .expect("Failed to write flow");
+        let (header, steps) = deserialize_test_file(
+            "appId: app\nenv:\n  USER: root\n---\n\
+             - runFlow:\n    file: login.yml\n    env:\n      USER: admin\n\
+             - runFlow: login.yml\n\
+             - inputText: \"${USER}\"\n",
+        )
+        .unwrap();
+
+        let root = Variables::default().with_defaults(header.env.as_ref());
+        let flattened = flatten_steps(
+            steps,
+            &dir.join("main.yml"),
+            "idRoot0(test)".to_string(),
+            root,
+            None,
+        )
+        .await;
+        fs::remove_dir_all(&dir).expect("Failed to clean up test directory");
+
+        let texts: Vec<String> = flattened
+            .unwrap()
+            .0
+            .iter()
+            .map(
+                |flow_step| match flow_step.variables.interpolate_step(&flow_step.step) {
+                    Ok(Step::InputText { inputText }) => inputText.text().to_string(),
+                    other => panic!("unexpected step {:?}", other),
+                },
+            )
+            .collect();
+        assert_eq!(texts, vec!["admin 1234", "guest 1234", "root"]);
+    }
+}
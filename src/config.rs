@@ -1,25 +1,83 @@
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fs::File, io::Read};
 use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
 
+use crate::common::{get_content, CapsFile, ExecutionOptions, Platform, ServerOptions};
+use crate::error::{Result, RustPilotError};
+use crate::flutter::is_flutter_capabilities;
+
+/// Settings of a run that don't come from the test file
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    pub server: ServerOptions,
+    pub execution: ExecutionOptions,
+}
+
+/// Appium capabilities of a run, whatever caps file format they came from
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
+    /// `platformName` as written in the caps file
     pub platform_name: String,
+    /// W3C capabilities, as sent to Appium
     pub capabilities: HashMap<String, Value>,
 }
 
+/// Formats a caps file can be written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CapsFormat {
+    /// Raw Appium capabilities
+    Json,
+    /// [`CapsFile`] with `platform`, `app_path` and `custom_caps`
+    Yaml,
+}
+
+impl CapsFormat {
+    /// Picks the format from the extension, falling back to the content
+    pub fn detect(caps_path: &str, contents: &str) -> Self {
+        match Path::new(caps_path)
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_lowercase)
+            .as_deref()
+        {
+            Some("json") => CapsFormat::Json,
+            Some("yaml" | "yml") => CapsFormat::Yaml,
+            _ if contents.trim_start().starts_with('{') => CapsFormat::Json,
+            _ => CapsFormat::Yaml,
+        }
+    }
+}
+
 impl Config {
-    pub fn from_file(caps_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut caps_file = File::open(caps_path)?;
-        let mut caps_contents = String::new();
-        caps_file.read_to_string(&mut caps_contents)?;
+    /// Loads a JSON or YAML caps file
+    pub fn from_file(caps_path: &str) -> Result<Self> {
+        let caps_contents = get_content(caps_path)?;
+        Config::parse(caps_path, &caps_contents)
+    }
+
+    /// Parses caps file contents; `caps_path` picks the format and names the file in errors
+    pub fn parse(caps_path: &str, contents: &str) -> Result<Self> {
+        let capabilities = match CapsFormat::detect(caps_path, contents) {
+            CapsFormat::Json => {
+                serde_json::from_str(contents).map_err(|e| RustPilotError::parse(caps_path, e))?
+            }
+            CapsFormat::Yaml => serde_yaml::from_str::<CapsFile>(contents)
+                .map_err(|e| RustPilotError::parse(caps_path, e))?
+                .capabilities()?,
+        };
+        Config::from_capabilities(capabilities)
+    }
 
-        let capabilities: HashMap<String, Value> = serde_json::from_str(&caps_contents)?;
-        
+    pub fn from_capabilities(capabilities: HashMap<String, Value>) -> Result<Self> {
         let platform_name = capabilities
             .get("platformName")
             .and_then(|v| v.as_str())
-            .ok_or("Missing or invalid platformName in capabilities")?
+            .ok_or_else(|| {
+                RustPilotError::Config(
+                    "Missing or invalid platformName in capabilities".to_string(),
+                )
+            })?
             .to_string();
 
         Ok(Config {
@@ -27,4 +85,25 @@ impl Config {
             capabilities,
         })
     }
+
+    /// Lays `overrides` over the capabilities, as a `rust_pilot.toml` profile does
+    pub fn merge_capabilities(mut self, overrides: HashMap<String, Value>) -> Result<Self> {
+        self.capabilities.extend(overrides);
+        Config::from_capabilities(self.capabilities)
+    }
+
+    /// Backend the capabilities ask for; Flutter wins over the device platform
+    pub fn platform(&self) -> Result<Platform> {
+        if is_flutter_capabilities(&self.capabilities) {
+            return Ok(Platform::Flutter);
+        }
+        match self.platform_name.to_lowercase().as_str() {
+            "android" => Ok(Platform::Android),
+            "ios" => Ok(Platform::Ios),
+            _ => Err(RustPilotError::Config(format!(
+                "Invalid platform {}",
+                self.platform_name
+            ))),
+        }
+    }
 }
@@ -3,11 +3,12 @@ mod tests {
     use std::collections::HashMap;
     use std::fs::{self, File};
     use std::io::Write;
-    use std::path::Path;
     use serde_json::json;
     use serde_json::Value;
     
-    use crate::config::Config;
+    use crate::common::Platform;
+    use crate::config::{CapsFormat, Config};
+    use crate::error::RustPilotError;
 
     // Helper function to create a temporary config file
     fn create_test_config_file(file_path: &str, platform_name: &str) -> Result<(), Box<dyn std::error::Error>> {
@@ -98,4 +99,84 @@ mod tests {
         // Verify the error is returned for file not found
         assert!(config_result.is_err());
     }
+
+    #[test]
+    fn test_config_from_yaml_caps_file() {
+        let config_path = "test_config.caps.yaml";
+        let yaml_content = r#"
+platform: "android"
+app_path: "./app/wikipedia.apk"
+platform_version: "9"
+automation_name: "UiAutomator2"
+full_reset: true
+custom_caps:
+  - key: appium:appPackage
+    value: org.wikipedia
+  - key: appium:autoGrantPermissions
+    value: true
+  - key: appium:newCommandTimeout
+    value: 300
+"#;
+        fs::write(config_path, yaml_content).expect("Failed to create test config file");
+
+        let config_result = Config::from_file(config_path);
+        fs::remove_file(config_path).expect("Failed to remove test config file");
+
+        let config = config_result.expect("YAML caps file should load");
+        assert_eq!(config.platform_name, "Android");
+        assert_eq!(config.platform().unwrap(), Platform::Android);
+        assert_eq!(
+            config.capabilities.get("appium:app"),
+            Some(&json!("./app/wikipedia.apk"))
+        );
+        assert_eq!(config.capabilities.get("platformVersion"), Some(&json!("9")));
+        assert_eq!(config.capabilities.get("appium:fullReset"), Some(&json!(true)));
+        assert_eq!(
+            config.capabilities.get("appium:automationName"),
+            Some(&json!("UiAutomator2"))
+        );
+        assert_eq!(
+            config.capabilities.get("appium:appPackage"),
+            Some(&json!("org.wikipedia"))
+        );
+        assert_eq!(config.capabilities.get("appium:newCommandTimeout"), Some(&json!(300)));
+    }
+
+    #[test]
+    fn test_caps_format_detection() {
+        assert_eq!(CapsFormat::detect("caps.json", "platform: android"), CapsFormat::Json);
+        assert_eq!(CapsFormat::detect("caps.YML", "{}"), CapsFormat::Yaml);
+        assert_eq!(
+            CapsFormat::detect("caps", r#"  {"platformName": "iOS"}"#),
+            CapsFormat::Json
+        );
+        assert_eq!(CapsFormat::detect("caps", "platform: ios"), CapsFormat::Yaml);
+
+        let config = Config::parse(
+            "caps",
+            "platform: ios\napp_path: App.app\nplatform_version: '17.0'\n",
+        )
+        .expect("YAML content should be detected");
+        assert_eq!(config.platform_name, "iOS");
+        assert_eq!(config.platform().unwrap(), Platform::Ios);
+    }
+
+    #[test]
+    fn test_config_platform() {
+        let config = Config::parse(
+            "caps.json",
+            r#"{"platformName": "android", "appium:automationName": "Flutter"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.platform().unwrap(), Platform::Flutter);
+
+        let config = Config::parse("caps.json", r#"{"platformName": "Windows"}"#).unwrap();
+        assert!(matches!(config.platform(), Err(RustPilotError::Config(_))));
+
+        let result = Config::parse(
+            "caps.yaml",
+            "platform: flutter\napp_path: a.apk\nplatform_version: '9'\n",
+        );
+        assert!(matches!(result, Err(RustPilotError::Config(_))));
+    }
 }
@@ -0,0 +1,128 @@
+//! Environment checks behind `rp doctor`
+
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+use crate::config::Config;
+use crate::error::{Result, RustPilotError};
+
+/// How long `doctor` waits for the Appium server to answer
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Host, port and base path of an Appium server URL
+#[derive(Debug, PartialEq, Eq)]
+pub struct ServerAddress {
+    pub host: String,
+    pub port: u16,
+    /// Always starts and ends with `/`
+    pub base_path: String,
+}
+
+impl ServerAddress {
+    pub fn parse(url: &str) -> Result<Self> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            RustPilotError::Config(format!("Appium URL must start with http://: {}", url))
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => {
+                let port = port.parse::<u16>().map_err(|_| {
+                    RustPilotError::Config(format!("Invalid port in Appium URL: {}", url))
+                })?;
+                (host, port)
+            }
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(RustPilotError::Config(format!(
+                "Missing host in Appium URL: {}",
+                url
+            )));
+        }
+        let base_path = if path.ends_with('/') {
+            path.to_string()
+        } else {
+            format!("{}/", path)
+        };
+        Ok(ServerAddress {
+            host: host.to_string(),
+            port,
+            base_path,
+        })
+    }
+}
+
+/// Asks the Appium server for its `/status`
+pub async fn check_appium(url: &str) -> Result<()> {
+    let address = ServerAddress::parse(url)?;
+    let unreachable = |reason: String| {
+        RustPilotError::Connection(format!(
+            "Appium server at {} is not reachable: {}",
+            url, reason
+        ))
+    };
+
+    let response = timeout(STATUS_TIMEOUT, async {
+        let mut stream = TcpStream::connect((address.host.as_str(), address.port)).await?;
+        let request = format!(
+            "GET {}status HTTP/1.0\r\nHost: {}\r\n\r\n",
+            address.base_path, address.host
+        );
+        stream.write_all(request.as_bytes()).await?;
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).await?;
+        Ok::<_, std::io::Error>(String::from_utf8_lossy(&response).into_owned())
+    })
+    .await
+    .map_err(|_| unreachable("timed out".to_string()))?
+    .map_err(|e| unreachable(e.to_string()))?;
+
+    let status_line = response.lines().next().unwrap_or_default();
+    if status_line.split_whitespace().nth(1) == Some("200") {
+        Ok(())
+    } else {
+        Err(unreachable(format!("unexpected answer {:?}", status_line)))
+    }
+}
+
+/// Loads a caps file and checks what can be checked without a device
+pub fn check_caps(caps_path: &str) -> Result<Config> {
+    let config = Config::from_file(caps_path)?;
+    let app = config
+        .capabilities
+        .get("appium:app")
+        .and_then(|value| value.as_str())
+        .ok_or_else(|| {
+            RustPilotError::Config("Missing or invalid appium:app in capabilities".to_string())
+        })?;
+    let is_remote = app.contains("://");
+    if !is_remote && !Path::new(app).exists() {
+        return Err(RustPilotError::Config(format!(
+            "App file {} does not exist",
+            app
+        )));
+    }
+    Ok(config)
+}
+
+/// Platform tool needed to talk to devices of `platform_name`, if any
+pub fn platform_tool(platform_name: &str) -> Option<&'static str> {
+    match platform_name.to_lowercase().as_str() {
+        "android" => Some("adb"),
+        "ios" => Some("xcrun"),
+        _ => None,
+    }
+}
+
+/// Whether `tool` can be started from the `PATH`
+pub fn tool_available(tool: &str) -> bool {
+    Command::new(tool).arg("--version").output().is_ok()
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::doctor::{check_caps, platform_tool, ServerAddress};
+    use crate::error::RustPilotError;
+
+    #[test]
+    fn test_server_address_parse() {
+        assert_eq!(
+            ServerAddress::parse("http://localhost:4723/").unwrap(),
+            ServerAddress {
+                host: "localhost".to_string(),
+                port: 4723,
+                base_path: "/".to_string(),
+            }
+        );
+        assert_eq!(
+            ServerAddress::parse("http://grid.local/wd/hub").unwrap(),
+            ServerAddress {
+                host: "grid.local".to_string(),
+                port: 80,
+                base_path: "/wd/hub/".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_server_address_parse_errors() {
+        for url in [
+            "localhost:4723",
+            "https://localhost:4723",
+            "http://:4723/",
+            "http://host:port/",
+        ] {
+            assert!(
+                matches!(ServerAddress::parse(url), Err(RustPilotError::Config(_))),
+                "{} should be rejected",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_caps_reports_missing_app_file() {
+        let caps_path = "test_doctor_caps.json";
+        fs::write(
+            caps_path,
+            r#"{"platformName": "Android", "appium:app": "/does/not/exist.apk"}"#,
+        )
+        .expect("Failed to write caps file");
+
+        let result = check_caps(caps_path);
+        fs::remove_file(caps_path).expect("Failed to clean up caps file");
+
+        assert!(
+            matches!(result, Err(RustPilotError::Config(message)) if message.contains("/does/not/exist.apk"))
+        );
+    }
+
+    #[test]
+    fn test_check_caps_accepts_remote_app() {
+        let caps_path = "test_doctor_remote_caps.json";
+        fs::write(
+            caps_path,
+            r#"{"platformName": "iOS", "appium:app": "https://example.com/app.zip"}"#,
+        )
+        .expect("Failed to write caps file");
+
+        let result = check_caps(caps_path);
+        fs::remove_file(caps_path).expect("Failed to clean up caps file");
+
+        assert_eq!(result.unwrap().platform_name, "iOS");
+        assert_eq!(platform_tool("iOS"), Some("xcrun"));
+        assert_eq!(platform_tool("windows"), None);
+    }
+}
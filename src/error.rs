@@ -0,0 +1,141 @@
+//! Error type shared by the whole library
+//!
+//! Library functions never exit the process; they return a [`RustPilotError`]
+//! and leave it to the binary to decide how to report it and which exit code to use.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use fantoccini::error::CmdError;
+
+use crate::common::driver::Selector;
+
+/// Everything that can go wrong while loading, resolving or running a test
+#[derive(Debug)]
+pub enum RustPilotError {
+    /// A file could not be read or written
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// YAML or JSON content could not be parsed
+    Parse { context: String, message: String },
+    /// A `runFlow` target could not be resolved
+    FlowResolution {
+        path: PathBuf,
+        /// Line of the offending `runFlow`, when known
+        line: Option<usize>,
+        message: String,
+    },
+    /// Missing or invalid configuration or capabilities
+    Config(String),
+    /// No session could be opened with the Appium server
+    Connection(String),
+    /// No element matched a selector
+    ElementNotFound { selector: String },
+    /// An assertion on the UI did not hold
+    Assertion(String),
+    /// A wait exceeded its timeout
+    Timeout(String),
+    /// A WebDriver command was rejected by the server
+    Command(CmdError),
+}
+
+/// Result alias used across the crate
+pub type Result<T> = std::result::Result<T, RustPilotError>;
+
+impl RustPilotError {
+    pub fn io(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        RustPilotError::Io {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn parse(context: impl Into<String>, message: impl fmt::Display) -> Self {
+        RustPilotError::Parse {
+            context: context.into(),
+            message: message.to_string(),
+        }
+    }
+
+    /// A `runFlow` at `line` of the flow file `path` could not be resolved
+    pub fn flow_resolution_at(
+        path: impl Into<PathBuf>,
+        line: Option<usize>,
+        message: impl fmt::Display,
+    ) -> Self {
+        RustPilotError::FlowResolution {
+            path: path.into(),
+            line,
+            message: message.to_string(),
+        }
+    }
+
+    /// No element matched any of `selectors`
+    pub fn element_not_found(selectors: &[Selector]) -> Self {
+        RustPilotError::ElementNotFound {
+            selector: selectors
+                .iter()
+                .map(Selector::to_string)
+                .collect::<Vec<_>>()
+                .join(" or "),
+        }
+    }
+}
+
+impl fmt::Display for RustPilotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RustPilotError::Io { path, source } => {
+                write!(f, "I/O error on {}: {}", path.display(), source)
+            }
+            RustPilotError::Parse { context, message } => {
+                write!(f, "Error parsing {}: {}", context, message)
+            }
+            RustPilotError::FlowResolution {
+                path,
+                line: Some(line),
+                message,
+            } => write!(
+                f,
+                "Error resolving flow {}:{}: {}",
+                path.display(),
+                line,
+                message
+            ),
+            RustPilotError::FlowResolution { path, message, .. } => {
+                write!(f, "Error resolving flow {}: {}", path.display(), message)
+            }
+            RustPilotError::Config(message) => write!(f, "Configuration error: {}", message),
+            RustPilotError::Connection(message) => {
+                write!(f, "Failed to connect to Appium: {}", message)
+            }
+            RustPilotError::ElementNotFound { selector } => {
+                write!(f, "Element not found: {}", selector)
+            }
+            RustPilotError::Assertion(message) => write!(f, "Assertion failed: {}", message),
+            RustPilotError::Timeout(message) => write!(f, "Timed out: {}", message),
+            RustPilotError::Command(err) => write!(f, "Driver command failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RustPilotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RustPilotError::Io { source, .. } => Some(source),
+            RustPilotError::Command(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CmdError> for RustPilotError {
+    fn from(err: CmdError) -> Self {
+        match err {
+            CmdError::WaitTimeout => RustPilotError::Timeout("waiting for the device".to_string()),
+            err => RustPilotError::Command(err),
+        }
+    }
+}
@@ -0,0 +1,400 @@
+//! Flutter backend built on the Appium Flutter driver
+//!
+//! Selectors are translated into Flutter finders (`byValueKey`, `byText`,
+//! `byType`, `byTooltip`, `bySemanticsLabel`) and resolved in the `FLUTTER`
+//! context. Selectors without a Flutter equivalent (XPath, index) and
+//! gestures fall back to the `NATIVE_APP` context of the underlying platform.
+
+#[cfg(test)]
+mod mod_test;
+
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use appium_client::capabilities::android::AndroidCapabilities;
+use appium_client::capabilities::ios::IOSCapabilities;
+use appium_client::capabilities::{AppCapable, AppiumCapability};
+use appium_client::commands::contexts::SupportsContextSwitching;
+use appium_client::Client;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use colored::Colorize;
+use fantoccini::{elements::Element, error::CmdError};
+use serde_json::{json, Map, Value};
+use tokio::sync::Mutex;
+
+use crate::android::get_android_element_by;
+use crate::common::*;
+use crate::config::RunOptions;
+use crate::error::RustPilotError;
+use crate::ios::get_ios_element_by;
+use crate::logger::Logger;
+use crate::reporting::RunResult;
+
+pub const FLUTTER_CONTEXT: &str = "FLUTTER";
+pub const NATIVE_CONTEXT: &str = "NATIVE_APP";
+
+/// How long a single Flutter lookup lets `flutter:waitFor` look for the widget
+const PROBE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Finders understood by the Appium Flutter driver
+#[derive(Clone, Debug, PartialEq)]
+pub enum FlutterFinder {
+    ByValueKey(String),
+    ByText(String),
+    ByType(String),
+    ByTooltip(String),
+    BySemanticsLabel(String),
+}
+
+impl FlutterFinder {
+    /// JSON description of the finder as expected by the Flutter driver
+    pub fn to_json(&self) -> Value {
+        match self {
+            FlutterFinder::ByValueKey(key) => json!({
+                "finderType": "ByValueKey",
+                "keyValueString": key,
+                "keyValueType": "String",
+            }),
+            FlutterFinder::ByText(text) => json!({
+                "finderType": "ByText",
+                "text": text,
+            }),
+            FlutterFinder::ByType(widget_type) => json!({
+                "finderType": "ByType",
+                "type": widget_type,
+            }),
+            FlutterFinder::ByTooltip(text) => json!({
+                "finderType": "ByTooltipMessage",
+                "text": text,
+            }),
+            FlutterFinder::BySemanticsLabel(label) => json!({
+                "finderType": "BySemanticsLabel",
+                "isRegExp": false,
+                "label": label,
+            }),
+        }
+    }
+
+    /// Base64 encoded finder, used by the Flutter driver as the element id
+    pub fn serialize(&self) -> String {
+        BASE64.encode(self.to_json().to_string())
+    }
+}
+
+/// Maps a shared selector to a Flutter finder, or `None` when only the native context can resolve it
+pub fn get_flutter_finder(selector: &Selector) -> Option<FlutterFinder> {
+    match selector {
+        Selector::Id { id } | Selector::IdWithIndex { id, .. } => {
+            Some(FlutterFinder::ByValueKey(id.clone()))
+        }
+        Selector::Text { text } => Some(FlutterFinder::ByText(text.clone())),
+        // Hint text is rendered as a plain Text widget inside the InputDecorator
+        Selector::Hint { hint } => Some(FlutterFinder::ByText(hint.clone())),
+        Selector::ClassName { className, .. } => Some(FlutterFinder::ByType(className.clone())),
+        Selector::Tooltip { tooltip } => Some(FlutterFinder::ByTooltip(tooltip.clone())),
+        Selector::Description { description } => {
+            Some(FlutterFinder::BySemanticsLabel(description.clone()))
+        }
+        Selector::AccessibilityId { accessibilityId } => {
+            Some(FlutterFinder::BySemanticsLabel(accessibilityId.clone()))
+        }
+        Selector::Xpath { .. } | Selector::Index { .. } => None,
+    }
+}
+
+/// Appium session hosting the Flutter app
+pub enum FlutterSession {
+    Android(Client<AndroidCapabilities>),
+    Ios(Client<IOSCapabilities>),
+}
+
+/// [`Driver`] implementation backed by the Appium Flutter driver
+pub struct FlutterDriver {
+    session: FlutterSession,
+    context: Mutex<String>,
+}
+
+impl FlutterDriver {
+    pub fn new(session: FlutterSession) -> Self {
+        FlutterDriver {
+            session,
+            context: Mutex::new(NATIVE_CONTEXT.to_string()),
+        }
+    }
+
+    /// Switches the session context, skipping the round trip when already there
+    pub async fn switch_context(&self, context: &str) -> Result<(), CmdError> {
+        let mut current = self.context.lock().await;
+        if *current == context {
+            return Ok(());
+        }
+        match &self.session {
+            FlutterSession::Android(client) => client.set_context(context).await?,
+            FlutterSession::Ios(client) => client.set_context(context).await?,
+        }
+        *current = context.to_string();
+        Ok(())
+    }
+
+    async fn find_native_once(&self, selector: &Selector) -> Result<Option<Element>, CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        let by = match self.native_platform() {
+            Platform::Ios => get_ios_element_by(selector.clone()),
+            _ => get_android_element_by(selector.clone()),
+        };
+        find_once_by(self.client(), by).await
+    }
+
+    /// Waits up to `timeout` for the widget of `finder` to be rendered
+    async fn wait_for_finder(
+        &self,
+        finder: FlutterFinder,
+        timeout: Duration,
+    ) -> Result<Element, CmdError> {
+        self.switch_context(FLUTTER_CONTEXT).await?;
+        let finder = finder.serialize();
+        self.client()
+            .execute(
+                "flutter:waitFor",
+                vec![json!(finder), json!(timeout.as_millis() as u64)],
+            )
+            .await?;
+        Ok(Element::from_element_id(
+            self.client().clone(),
+            finder.into(),
+        ))
+    }
+}
+
+#[async_trait]
+impl Driver for FlutterDriver {
+    fn platform(&self) -> Platform {
+        Platform::Flutter
+    }
+
+    fn native_platform(&self) -> Platform {
+        match self.session {
+            FlutterSession::Android(_) => Platform::Android,
+            FlutterSession::Ios(_) => Platform::Ios,
+        }
+    }
+
+    fn client(&self) -> &fantoccini::Client {
+        match &self.session {
+            FlutterSession::Android(client) => client,
+            FlutterSession::Ios(client) => client,
+        }
+    }
+
+    async fn find_once(&self, selector: &Selector) -> Result<Option<Element>, CmdError> {
+        let Some(finder) = get_flutter_finder(selector) else {
+            return self.find_native_once(selector).await;
+        };
+        match self.wait_for_finder(finder, PROBE_TIMEOUT).await {
+            Ok(element) => Ok(Some(element)),
+            Err(err) if is_widget_missing(&err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn find(&self, selector: &Selector, wait: WaitOptions) -> Result<Element, CmdError> {
+        let Some(finder) = get_flutter_finder(selector) else {
+            return wait_until(wait, || self.find_native_once(selector))
+                .await?
+                .ok_or(CmdError::WaitTimeout);
+        };
+        // The Flutter driver polls by itself
+        self.wait_for_finder(finder, wait.timeout).await
+    }
+
+    async fn is_visible(&self, element: &Element) -> Result<bool, CmdError> {
+        // Flutter elements are only returned once `flutter:waitFor` saw them rendered
+        if *self.context.lock().await == FLUTTER_CONTEXT {
+            return Ok(true);
+        }
+        element.is_displayed().await
+    }
+
+    async fn element_state(
+        &self,
+        element: &Element,
+        state: ElementState,
+    ) -> Result<bool, CmdError> {
+        if *self.context.lock().await == FLUTTER_CONTEXT {
+            return Err(CmdError::InvalidArgument(
+                state.name().to_string(),
+                "Flutter finders can't filter on element states".to_string(),
+            ));
+        }
+        let (attribute, on) = state_attribute(self.native_platform(), state);
+        Ok(element.attr(attribute).await?.as_deref() == Some(on))
+    }
+
+    async fn element_rect(
+        &self,
+        element: &Element,
+    ) -> Result<Option<(f64, f64, f64, f64)>, CmdError> {
+        // Flutter finders have no WebDriver bounds
+        if *self.context.lock().await == FLUTTER_CONTEXT {
+            return Ok(None);
+        }
+        element.rectangle().await.map(Some)
+    }
+
+    async fn type_text(&self, text: &str) -> Result<(), CmdError> {
+        if *self.context.lock().await == FLUTTER_CONTEXT {
+            self.client()
+                .execute("flutter:enterText", vec![json!(text)])
+                .await?;
+            return Ok(());
+        }
+        self.client().active_element().await?.send_keys(text).await
+    }
+
+    async fn swipe(
+        &self,
+        from: (i64, i64),
+        to: (i64, i64),
+        duration: Duration,
+    ) -> Result<(), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        self.client()
+            .perform_actions(swipe_actions(from, to, duration))
+            .await
+    }
+
+    async fn window_size(&self) -> Result<(u64, u64), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        self.client().get_window_size().await
+    }
+
+    async fn page_source(&self) -> Result<String, CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        self.client().source().await
+    }
+
+    async fn stop_app(&self, app_id: &str) -> Result<(), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        stop_app(self.client(), self.native_platform(), app_id).await
+    }
+
+    async fn clear_app(&self, app_id: &str) -> Result<(), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        clear_app(self.client(), self.native_platform(), app_id).await
+    }
+
+    async fn launch_app(
+        &self,
+        app_id: &str,
+        arguments: &Map<String, Value>,
+    ) -> Result<(), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        launch_app(self.client(), self.native_platform(), app_id, arguments).await
+    }
+
+    async fn set_permissions(
+        &self,
+        app_id: &str,
+        permissions: &BTreeMap<String, PermissionState>,
+    ) -> Result<(), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        set_permissions(self.client(), self.native_platform(), app_id, permissions).await
+    }
+
+    async fn press_key(&self, key: Key) -> Result<(), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        press_key(self.client(), self.native_platform(), key).await
+    }
+
+    async fn hide_keyboard(&self) -> Result<(), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        hide_keyboard(self.client()).await
+    }
+
+    async fn erase_text(&self, count: usize) -> Result<(), CmdError> {
+        self.switch_context(NATIVE_CONTEXT).await?;
+        erase_text(self.client(), self.native_platform(), count).await
+    }
+}
+
+/// Whether `err` only means the widget isn't rendered
+///
+/// A widget that isn't rendered makes `flutter:waitFor` time out; any other
+/// error, such as a lost session or a crashed Flutter driver, is a real one.
+pub fn is_widget_missing(err: &CmdError) -> bool {
+    match err {
+        CmdError::NoSuchElement(_) | CmdError::WaitTimeout => true,
+        CmdError::Standard(err) => match err.error() {
+            "timeout" | "script timeout" => true,
+            // The Flutter driver reports its own timeouts as unknown errors
+            "unknown error" => is_wait_for_timeout(&err.message),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether a Flutter driver error message reports a `flutter:waitFor` timeout
+fn is_wait_for_timeout(message: &str) -> bool {
+    let message = message.to_lowercase();
+    message.contains("waitfor") && message.contains("timeout")
+}
+
+/// Whether a caps file asks for the Appium Flutter driver
+pub fn is_flutter_capabilities(capabilities: &HashMap<String, Value>) -> bool {
+    ["appium:automationName", "automationName"]
+        .iter()
+        .filter_map(|key| capabilities.get(*key).and_then(|value| value.as_str()))
+        .any(|name| name.eq_ignore_ascii_case("flutter"))
+}
+
+pub async fn launch_flutter_main(
+    capabilities: &HashMap<String, Value>,
+    steps: Vec<FlowStep>,
+    options: &RunOptions,
+) -> Result<RunResult, RustPilotError> {
+    let app_path = required_capability(capabilities, &["appium:app", "app"])?;
+    let platform_name = required_capability(capabilities, &["platformName"])?.to_lowercase();
+
+    Logger::progress(format!("{} App path: {}", info_tag(), app_path.blue()));
+    Logger::progress(format!("⏳ Launching Flutter app on {}", platform_name));
+    let session = match platform_name.as_str() {
+        "android" => {
+            let mut caps = AndroidCapabilities::new();
+            set_capabilities(&mut caps, capabilities, &[]);
+            caps.automation_name("Flutter");
+            caps.app(app_path);
+            let client = connect(caps, &options.server).await?;
+            FlutterSession::Android(client)
+        }
+        "ios" => {
+            let mut caps = IOSCapabilities::new();
+            set_capabilities(&mut caps, capabilities, &[]);
+            caps.automation_name("Flutter");
+            caps.app(app_path);
+            let client = connect(caps, &options.server).await?;
+            FlutterSession::Ios(client)
+        }
+        other => {
+            return Err(RustPilotError::Config(format!(
+                "Flutter is not supported on {}",
+                other
+            )))
+        }
+    };
+    Logger::progress("✓ Flutter app launched successfully");
+
+    let driver = FlutterDriver::new(session);
+    let mut run = execute_steps(
+        &driver,
+        steps,
+        &options.execution,
+        &ExecutionContext::default(),
+    )
+    .await;
+    disconnect(driver.client()).await;
+    run.device = device_name(capabilities);
+    Ok(run)
+}
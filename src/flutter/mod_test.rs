@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use base64::engine::general_purpose::STANDARD as BASE64;
+    use base64::Engine;
+    use fantoccini::error::CmdError;
+    use serde_json::{json, Value};
+
+    use crate::common::Selector;
+    use crate::flutter::{
+        get_flutter_finder, is_flutter_capabilities, is_wait_for_timeout, is_widget_missing,
+        FlutterFinder,
+    };
+
+    #[test]
+    fn test_flutter_finder_from_id() {
+        let selector = Selector::Id {
+            id: "login_button".to_string(),
+        };
+        assert_eq!(
+            get_flutter_finder(&selector),
+            Some(FlutterFinder::ByValueKey("login_button".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_flutter_finder_from_class_name() {
+        let selector = Selector::ClassName {
+            className: "ElevatedButton".to_string(),
+            instance: None,
+        };
+        assert_eq!(
+            get_flutter_finder(&selector),
+            Some(FlutterFinder::ByType("ElevatedButton".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_flutter_finder_from_tooltip_and_description() {
+        let tooltip = Selector::Tooltip {
+            tooltip: "Back".to_string(),
+        };
+        let description = Selector::Description {
+            description: "Profile picture".to_string(),
+        };
+        assert_eq!(
+            get_flutter_finder(&tooltip),
+            Some(FlutterFinder::ByTooltip("Back".to_string()))
+        );
+        assert_eq!(
+            get_flutter_finder(&description),
+            Some(FlutterFinder::BySemanticsLabel(
+                "Profile picture".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_flutter_finder_falls_back_to_native_for_xpath() {
+        let selector = Selector::Xpath {
+            xpath: "//android.widget.Button".to_string(),
+        };
+        assert_eq!(get_flutter_finder(&selector), None);
+    }
+
+    #[test]
+    fn test_flutter_finder_serialization() {
+        let finder = FlutterFinder::ByValueKey("counter".to_string());
+        let decoded = BASE64.decode(finder.serialize()).unwrap();
+        let value: Value = serde_json::from_slice(&decoded).unwrap();
+
+        assert_eq!(
+            value,
+            json!({
+                "finderType": "ByValueKey",
+                "keyValueString": "counter",
+                "keyValueType": "String",
+            })
+        );
+    }
+
+    #[test]
+    fn test_is_flutter_capabilities() {
+        let mut capabilities: HashMap<String, Value> = HashMap::new();
+        capabilities.insert("platformName".to_string(), json!("Android"));
+        assert!(!is_flutter_capabilities(&capabilities));
+
+        capabilities.insert("appium:automationName".to_string(), json!("Flutter"));
+        assert!(is_flutter_capabilities(&capabilities));
+    }
+
+    #[test]
+    fn test_only_timeouts_mean_a_missing_widget() {
+        assert!(is_widget_missing(&CmdError::WaitTimeout));
+
+        let lost = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        assert!(!is_widget_missing(&CmdError::Lost(lost)));
+        assert!(!is_widget_missing(&CmdError::NotJson(
+            "502 Bad Gateway".to_string()
+        )));
+    }
+
+    #[test]
+    fn test_wait_for_timeouts_match_in_any_casing() {
+        assert!(is_wait_for_timeout(
+            "Timeout while waiting for waitFor to find the widget"
+        ));
+        assert!(is_wait_for_timeout("flutter:waitFor timeout after 5000 ms"));
+        assert!(is_wait_for_timeout("WAITFOR TIMEOUT"));
+        assert!(!is_wait_for_timeout("waitFor failed: the isolate crashed"));
+        assert!(!is_wait_for_timeout("Timeout reading the Dart VM service"));
+    }
+}
@@ -0,0 +1,37 @@
+use appium_client::{capabilities::ios::IOSCapabilities, Client};
+use async_trait::async_trait;
+use fantoccini::{elements::Element, error::CmdError};
+
+use crate::common::{find_once_by, Driver, Platform, Selector};
+use crate::ios::get_ios_element_by;
+
+/// [`Driver`] implementation backed by an XCUITest session
+pub struct IosDriver {
+    client: Client<IOSCapabilities>,
+}
+
+impl IosDriver {
+    pub fn new(client: Client<IOSCapabilities>) -> Self {
+        IosDriver { client }
+    }
+}
+
+#[async_trait]
+impl Driver for IosDriver {
+    fn platform(&self) -> Platform {
+        Platform::Ios
+    }
+
+    fn client(&self) -> &fantoccini::Client {
+        &self.client
+    }
+
+    async fn find_once(&self, selector: &Selector) -> Result<Option<Element>, CmdError> {
+        find_once_by(&self.client, get_ios_element_by(selector.clone())).await
+    }
+
+    async fn type_text(&self, text: &str) -> Result<(), CmdError> {
+        // XCUITest has no "mobile: type", so type into whatever holds the focus
+        self.client.active_element().await?.send_keys(text).await
+    }
+}
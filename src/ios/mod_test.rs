@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use crate::common::CustomCapability;
+    use crate::common::CustomCapabilityValue;
+    use crate::ios::{get_ios_element_by, set_custom_capabilities_ios, IosElementSelector};
+    use appium_client::capabilities::ios::IOSCapabilities;
+
+    #[test]
+    fn test_ios_element_selector_text() {
+        let selector = IosElementSelector::Text {
+            text: "Submit".to_string(),
+        };
+        let by = get_ios_element_by(selector);
+
+        let by_debug = format!("{:?}", by);
+        assert!(by_debug.contains("label MATCHES"));
+        assert!(by_debug.contains("Submit"));
+    }
+
+    #[test]
+    fn test_ios_element_selector_text_escapes_quotes() {
+        let selector = IosElementSelector::Text {
+            text: "Say \"hi\"".to_string(),
+        };
+        let by = get_ios_element_by(selector);
+
+        let by_debug = format!("{:?}", by);
+        assert!(by_debug.contains(r#"Say \\\"hi\\\""#));
+    }
+
+    #[test]
+    fn test_ios_element_selector_hint() {
+        let selector = IosElementSelector::Hint {
+            hint: "Email".to_string(),
+        };
+        let by = get_ios_element_by(selector);
+
+        let by_debug = format!("{:?}", by);
+        assert!(by_debug.contains("placeholderValue"));
+        assert!(by_debug.contains("Email"));
+    }
+
+    #[test]
+    fn test_ios_element_selector_id_with_index_is_one_based() {
+        let selector = IosElementSelector::IdWithIndex {
+            id: "cell".to_string(),
+            index: 0,
+        };
+        let by = get_ios_element_by(selector);
+
+        let by_debug = format!("{:?}", by);
+        assert!(by_debug.contains("cell"));
+        assert!(by_debug.contains("[1]"));
+    }
+
+    #[test]
+    fn test_ios_element_selector_class_name_with_instance() {
+        let selector = IosElementSelector::ClassName {
+            className: "XCUIElementTypeButton".to_string(),
+            instance: Some(2),
+        };
+        let by = get_ios_element_by(selector);
+
+        let by_debug = format!("{:?}", by);
+        assert!(by_debug.contains("**/XCUIElementTypeButton[3]"));
+    }
+
+    #[test]
+    fn test_set_custom_capabilities_ios_all_value_types() {
+        let mut caps = IOSCapabilities::new_xcui();
+        let custom_caps = vec![
+            CustomCapability {
+                key: "appium:noReset".to_string(),
+                value: CustomCapabilityValue::BooleanValue(true),
+            },
+            CustomCapability {
+                key: "appium:deviceName".to_string(),
+                value: CustomCapabilityValue::StringValue("iPhone 15".to_string()),
+            },
+            CustomCapability {
+                key: "appium:wdaLaunchTimeout".to_string(),
+                value: CustomCapabilityValue::NumberValue(60000.0),
+            },
+            CustomCapability {
+                key: "appium:someNull".to_string(),
+                value: CustomCapabilityValue::NullValue,
+            },
+        ];
+
+        set_custom_capabilities_ios(&mut caps, custom_caps);
+
+        assert_eq!(caps.get("appium:noReset"), Some(&serde_json::json!(true)));
+        assert_eq!(
+            caps.get("appium:deviceName"),
+            Some(&serde_json::json!("iPhone 15"))
+        );
+    }
+}
@@ -1,9 +1,27 @@
 pub mod android;
+pub mod cli;
+#[cfg(test)]
+mod cli_test;
 pub mod common;
 pub mod config;
+#[cfg(test)]
+mod config_test;
+pub mod doctor;
+#[cfg(test)]
+mod doctor_test;
+pub mod error;
+pub mod flutter;
+pub mod ios;
 pub mod logger;
+pub mod project;
+#[cfg(test)]
+mod project_test;
 pub mod reporting;
 #[cfg(test)]
 mod reporting_test;
-// TODO: pub mod flutter;
-// TODO: pub mod ios;
+pub mod secrets;
+#[cfg(test)]
+mod secrets_test;
+pub mod suite;
+#[cfg(test)]
+mod suite_test;
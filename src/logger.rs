@@ -1,56 +1,111 @@
 use colored::Colorize;
 use std::fmt::Display;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+
+use crate::secrets;
+
+/// When set, stdout is reserved for machine-readable output and logs go to stderr
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// How much progress output is printed; errors and warnings are always shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Verbosity {
+    /// Only warnings, errors and the final summary
+    Quiet = 0,
+    Normal = 1,
+    /// Also debug messages
+    Verbose = 2,
+}
 
 pub struct Logger;
 
 impl Logger {
+    pub fn set_verbosity(verbosity: Verbosity) {
+        VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+    }
+
+    fn enabled(verbosity: Verbosity) -> bool {
+        VERBOSITY.load(Ordering::Relaxed) >= verbosity as u8
+    }
+
+    /// Prints a progress line, unless running quietly
+    pub fn progress<T: Display>(message: T) {
+        if Logger::enabled(Verbosity::Normal) {
+            Logger::print(message);
+        }
+    }
+
+    /// Sends every log line to stderr, keeping stdout free for `--json-stdout`
+    pub fn reserve_stdout(reserved: bool) {
+        STDOUT_RESERVED.store(reserved, Ordering::Relaxed);
+    }
+
+    /// Prints a line of human readable output, with secrets masked
+    pub fn print<T: Display>(message: T) {
+        let message = secrets::mask(&message.to_string());
+        if STDOUT_RESERVED.load(Ordering::Relaxed) {
+            eprintln!("{}", message);
+        } else {
+            println!("{}", message);
+        }
+    }
+
+    // Debug messages - dimmed, only shown with --verbose
+    pub fn debug<T: Display>(message: T) {
+        if Logger::enabled(Verbosity::Verbose) {
+            Logger::print(format!("🔍 {}", message.to_string().dimmed()));
+        }
+    }
+
     // Info messages - blue color with ℹ️ icon
     pub fn info<T: Display>(message: T) {
-        println!("ℹ️  {}", message.to_string().blue());
+        Logger::progress(format!("ℹ️  {}", message.to_string().blue()));
     }
 
     pub fn info_with_indent<T: Display>(message: T, indent_level: usize) {
         let indent = "  ".repeat(indent_level);
-        println!("{}ℹ️  {}", indent, message.to_string().blue());
+        Logger::progress(format!("{}ℹ️  {}", indent, message.to_string().blue()));
     }
 
     // Success messages - green color with ✅ icon
     pub fn success<T: Display>(message: T) {
-        println!("✅ {}", message.to_string().green());
+        Logger::progress(format!("✅ {}", message.to_string().green()));
     }
 
     pub fn success_with_indent<T: Display>(message: T, indent_level: usize) {
         let indent = "  ".repeat(indent_level);
-        println!("{}✅ {}", indent, message.to_string().green());
+        Logger::progress(format!("{}✅ {}", indent, message.to_string().green()));
     }
 
     // Error messages - red color with ❌ icon
     pub fn error<T: Display>(message: T) {
-        eprintln!("❌ {}", message.to_string().red());
+        eprintln!("❌ {}", secrets::mask(&message.to_string()).red());
     }
 
     pub fn error_with_indent<T: Display>(message: T, indent_level: usize) {
         let indent = "  ".repeat(indent_level);
-        eprintln!("{}❌ {}", indent, message.to_string().red());
+        eprintln!("{}❌ {}", indent, secrets::mask(&message.to_string()).red());
     }
 
     // Warning messages - yellow color with ⚠️ icon
     pub fn warning<T: Display>(message: T) {
-        println!("⚠️  {}", message.to_string().yellow());
+        Logger::print(format!("⚠️  {}", message.to_string().yellow()));
     }
 
     pub fn warning_with_indent<T: Display>(message: T, indent_level: usize) {
         let indent = "  ".repeat(indent_level);
-        println!("{}⚠️  {}", indent, message.to_string().yellow());
+        Logger::print(format!("{}⚠️  {}", indent, message.to_string().yellow()));
     }
 
     // Step messages - cyan color with 👉 icon
     pub fn step<T: Display>(message: T) {
-        println!("⏳ {}", message.to_string().cyan());
+        Logger::progress(format!("⏳ {}", message.to_string().cyan()));
     }
 
     pub fn step_with_indent<T: Display>(message: T, indent_level: usize) {
         let indent = "  ".repeat(indent_level);
-        println!("{}⏳ {}", indent, message.to_string().cyan());
+        Logger::progress(format!("{}⏳ {}", indent, message.to_string().cyan()));
     }
 }
@@ -1,21 +1,24 @@
 use std::{
     collections::HashMap,
-    env,
-    fs::File,
-    io::Read,
-    path::Path,
+    path::{Path, PathBuf},
     process,
-    time::Instant,
 };
 
-use chrono::Local;
 use colored::Colorize;
 use rust_pilot::{
     android::*,
-    common::{tags::*, *},
-
+    cli::{Cli, Command, ReportArgs, RunArgs, ServerArgs},
+    common::*,
+    config::{Config, RunOptions},
+    doctor::{check_appium, check_caps, platform_tool, tool_available},
+    error::RustPilotError,
+    flutter::*,
+    ios::*,
     logger::Logger,
-    reporting::TestReport,
+    project::{ProfileSettings, ProjectConfig, PROFILE_ENV, PROJECT_FILE_NAME},
+    reporting::{render_report, save_report, JsonRenderer, ReportFormat, RunResult},
+    secrets,
+    suite::discover_flows,
 };
 use serde_json::Value;
 
@@ -28,107 +31,432 @@ const LOGO: &str = r#"
  |_|  \_\  \__,_| |___/  \__| |_|      |_| |_|  \___/   \__|
 "#;
 
-const USAGE: &str = "Usage: rp <caps_file> <test_file>";
+/// Exit code for failures of the test itself (assertions, missing elements, timeouts)
+const EXIT_TEST_FAILED: i32 = 1;
+/// Exit code for invalid input: arguments, caps file, test files
+const EXIT_INVALID_INPUT: i32 = 2;
+/// Exit code when the Appium server or the device can't be driven
+const EXIT_INFRASTRUCTURE: i32 = 3;
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() {
+    let cli = Cli::parse_args();
+    Logger::set_verbosity(cli.verbosity());
+
+    let outcome = match cli.command {
+        Command::Run(args) => run(args).await,
+        Command::Validate { test_files, caps } => validate(&test_files, caps.as_deref()).await,
+        Command::List { test_file } => list(&test_file).await,
+        Command::Graph { test_file } => graph(&test_file).await,
+        Command::Report {
+            json_report,
+            output,
+        } => report(&json_report, &output),
+        Command::Doctor { caps_file, server } => doctor(caps_file.as_deref(), &server).await,
+    };
+
+    match outcome {
+        Ok(true) => {}
+        Ok(false) => process::exit(EXIT_TEST_FAILED),
+        Err(err) => {
+            Logger::error(&err);
+            process::exit(exit_code(&err));
+        }
+    }
+}
+
+fn exit_code(err: &RustPilotError) -> i32 {
+    match err {
+        RustPilotError::ElementNotFound { .. }
+        | RustPilotError::Assertion(_)
+        | RustPilotError::Timeout(_) => EXIT_TEST_FAILED,
+        RustPilotError::Io { .. }
+        | RustPilotError::Parse { .. }
+        | RustPilotError::FlowResolution { .. }
+        | RustPilotError::Config(_) => EXIT_INVALID_INPUT,
+        RustPilotError::Connection(_) | RustPilotError::Command(_) => EXIT_INFRASTRUCTURE,
+    }
+}
+
+/// Parses a test file and resolves its `runFlow` steps
+///
+/// Returns the header, the flattened steps and the Mermaid graph of the flow tree.
+async fn load_flow(
+    test_file: &Path,
+    env: &Env,
+) -> Result<(TestFileHeader, Vec<FlowStep>, String), RustPilotError> {
+    let (header, steps) = parse_test_file(test_file)?;
+    let variables = Variables::default()
+        .with_overrides(Some(env))
+        .with_defaults(header.env.as_ref());
+
+    let (flattened_steps, flow_graph) = flatten_steps(
+        steps,
+        test_file,
+        root_node(&test_file.display().to_string()),
+        variables,
+        header.appId.clone(),
+    )
+    .await?;
+    Ok((header, flattened_steps, flow_graph))
+}
+
+/// Runs the test file, or every flow of a directory, returning whether every step passed
+async fn run(mut args: RunArgs) -> Result<bool, RustPilotError> {
+    Logger::reserve_stdout(args.json_stdout);
     display_startup_info();
 
-    let (caps_path, test_file_path) = parse_command_line_args()?;
+    let profile = load_profile(&args)?;
+    args.apply_profile(&profile);
+    profile
+        .secrets
+        .iter()
+        .for_each(|name| secrets::register_name(name));
+    let report_formats = args.output.report_formats()?;
+    let caps_file = args
+        .caps_file()
+        .map(Path::to_path_buf)
+        .or_else(|| profile.caps.clone())
+        .ok_or_else(|| {
+            RustPilotError::Config(format!(
+                "No caps file given and no profile of {} sets one",
+                PROJECT_FILE_NAME
+            ))
+        })?;
 
+    let caps_path = caps_file.display().to_string();
+    let test_file_path = args.test_file().display().to_string();
     Logger::info(format!("Caps file path: {}", caps_path));
     Logger::info(format!("Test file path: {}", test_file_path));
 
-    let (_header, steps) = parse_test_file(&test_file_path);
+    let test_files = if args.test_file().is_dir() {
+        let test_files = discover_flows(args.test_file(), &[&caps_file])?;
+        Logger::info(format!(
+            "Found {} flow(s) in {}",
+            test_files.len(),
+            test_file_path
+        ));
+        test_files
+    } else {
+        vec![args.test_file().to_path_buf()]
+    };
 
-    let base_path = Path::new(&test_file_path)
-        .parent()
-        .ok_or("Failed to determine base path")?;
+    // Every flow is loaded up front, so an invalid one fails the run before any device work
+    let env = args.variables()?;
+    let mut flows = Vec::new();
+    for test_file in test_files {
+        let (header, flattened_steps, flow_graph) = load_flow(&test_file, &env).await?;
+        if !header.matches_tags(&args.include_tags, &args.exclude_tags) {
+            Logger::warning(format!(
+                "Skipping {}: tags {:?} don't match the tag filters",
+                test_file.display(),
+                header.tags.unwrap_or_default()
+            ));
+            continue;
+        }
+        flows.push((test_file.display().to_string(), flattened_steps, flow_graph));
+    }
+    if flows.is_empty() {
+        return Ok(true);
+    }
 
-    let (flattened_steps, _) = flatten_steps(
-        steps,
-        base_path,
-        format!("idRoot0({})", base_path.display()),
-    )
-    .await;
-
-    let mut caps_file = File::open(caps_path)?;
-    let mut caps_contents = String::new();
-    caps_file.read_to_string(&mut caps_contents)?;
-
-    let capabilities_file: HashMap<String, Value> = serde_json::from_str(&caps_contents)?;
-
-    let start = Instant::now();
-    let (steps_count, report) = match capabilities_file.get("platformName") {
-        Some(Value::String(platform)) => match platform.as_str() {
-            "android" => launch_android_main(&capabilities_file, flattened_steps)
-                .await
-                .unwrap_or_else(|err| {
-                    eprintln!("{} Error launching Android test: {}", error_tag(), err);
-                    (
-                        0,
-                        format!("### ERROR LAUNCHING ANDROID TEST\n```{}```", err),
-                    )
-                }),
-            "ios" => {
-                todo!("### IOS NOT IMPLEMENTED")
+    let config = Config::from_file(&caps_path)?.merge_capabilities(profile.capabilities())?;
+    let server = ServerOptions::resolve(&args.server.overrides(), &config.capabilities, env_var)?;
+    let options = args.run_options(server);
+    Logger::debug(format!("Run options: {:?}", options));
+
+    let platform = config.platform()?;
+    let suite = flows.len() > 1;
+    let mut run = RunResult::new(platform.name().to_string());
+    let mut failed_flows = Vec::new();
+    for (flow_name, flattened_steps, flow_graph) in flows {
+        if suite {
+            Logger::info(format!("Running flow {}", flow_name.blue()));
+        }
+        let mut flow_run =
+            match launch(platform, &config.capabilities, flattened_steps, &options).await {
+                Ok(flow_run) => flow_run,
+                Err(err) => {
+                    // The flow never ran, but a report explaining why is still worth keeping
+                    let mut flow_run = RunResult::new(platform.name().to_string());
+                    flow_run.set_flow(flow_name);
+                    flow_run.flow_graph = Some(flow_graph);
+                    run.merge(flow_run);
+                    run.set_error(err.to_string());
+                    for report_name in publish_run(
+                        &run,
+                        &report_formats,
+                        args.output.report_dir(),
+                        args.json_stdout,
+                    )? {
+                        Logger::print(format!("    Report file: {}", report_name));
+                    }
+                    return Err(err);
+                }
+            };
+        if flow_run.failed_step().is_some() {
+            failed_flows.push(flow_name.clone());
+        }
+        flow_run.set_flow(flow_name);
+        flow_run.flow_graph = Some(flow_graph);
+        run.merge(flow_run);
+    }
+
+    let report_names = publish_run(
+        &run,
+        &report_formats,
+        args.output.report_dir(),
+        args.json_stdout,
+    )?;
+    let passed_count = run.passed_count();
+    let time = run.duration;
+
+    if let Some(failed_step) = run.failed_step() {
+        Logger::print(format!("\n\n{}", "Test suite failed".red()));
+        if suite {
+            Logger::print(format!("    Failed flows: {}", failed_flows.join(", ")));
+        }
+        Logger::print(format!("    Failed step: {}", failed_step.description));
+        if let Some(source) = &failed_step.source {
+            Logger::print(format!("    Declared at: {}", source));
+        }
+        if let Some(message) = &failed_step.message {
+            Logger::print(format!("    Error: {}", message.red()));
+        }
+        for report_name in &report_names {
+            Logger::print(format!("    Report file: {}", report_name));
+        }
+        Logger::print(format!("    Actions executed: {}", passed_count));
+        Logger::print(format!(
+            "    Total time elapsed: {:.2} seconds",
+            time.as_secs_f64()
+        ));
+        return Ok(false);
+    }
+
+    Logger::print(format!("\n\n{}", "Test suite runned successfully".green()));
+    if suite {
+        Logger::print(format!("    Flows executed: {}", run.flows.len()));
+    }
+    for report_name in &report_names {
+        Logger::print(format!("    Report file: {}", report_name));
+    }
+    Logger::print(format!("    Actions executed: {}", passed_count));
+    Logger::print(format!(
+        "    Total time elapsed: {:.2} seconds",
+        time.as_secs_f64()
+    ));
+    Ok(true)
+}
+
+/// Settings of the selected profile of the `rust_pilot.toml` found above the test file or directory
+///
+/// Without a project file, only an explicit `--profile` is an error.
+fn load_profile(args: &RunArgs) -> Result<ProfileSettings, RustPilotError> {
+    let profile_name = args.profile.clone().or_else(|| env_var(PROFILE_ENV));
+    let test_path = args.test_file();
+    let search_start = match test_path.parent() {
+        _ if test_path.is_dir() => test_path,
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let search_start = search_start
+        .canonicalize()
+        .map_err(|e| RustPilotError::io(search_start, e))?;
+    match ProjectConfig::discover(&search_start)? {
+        Some(project) => {
+            Logger::info(format!("Project file: {}", project.path.display()));
+            if let Some(name) = profile_name
+                .as_deref()
+                .or(project.default_profile.as_deref())
+            {
+                Logger::info(format!("Profile: {}", name));
             }
-            _ => {
-                eprintln!("{} Invalid platform", error_tag());
-                process::exit(1);
+            project.profile(profile_name.as_deref())
+        }
+        None if profile_name.is_some() => Err(RustPilotError::Config(format!(
+            "Profile {} was asked for but no {} was found",
+            profile_name.unwrap_or_default(),
+            PROJECT_FILE_NAME
+        ))),
+        None => Ok(ProfileSettings::default()),
+    }
+}
+
+/// Runs the steps on the backend picked from the caps file
+async fn launch(
+    platform: Platform,
+    capabilities: &HashMap<String, Value>,
+    steps: Vec<FlowStep>,
+    options: &RunOptions,
+) -> Result<RunResult, RustPilotError> {
+    match platform {
+        Platform::Android => launch_android_main(capabilities, steps, options).await,
+        Platform::Ios => launch_ios_main(capabilities, steps, options).await,
+        Platform::Flutter => launch_flutter_main(capabilities, steps, options).await,
+    }
+}
+
+/// Checks that every test file parses and its flows resolve
+async fn validate(test_files: &[PathBuf], caps: Option<&Path>) -> Result<bool, RustPilotError> {
+    let mut invalid = 0;
+    for test_file in test_files {
+        match load_flow(test_file, &Env::new()).await {
+            Ok((_, steps, _)) => {
+                Logger::success(format!("{}: {} steps", test_file.display(), steps.len()))
+            }
+            Err(err) => {
+                Logger::error(format!("{}: {}", test_file.display(), err));
+                invalid += 1;
             }
-        },
-        None => {
-            eprintln!("{} Missing platform key in caps file", error_tag());
-            process::exit(1);
         }
-        Some(_) => {
-            eprintln!("{} Invalid platform key in caps file", error_tag());
-            process::exit(1);
+    }
+    if let Some(caps) = caps {
+        match Config::from_file(&caps.display().to_string()) {
+            Ok(config) => Logger::success(format!(
+                "{}: {} capabilities",
+                caps.display(),
+                config.platform_name
+            )),
+            Err(err) => {
+                Logger::error(format!("{}: {}", caps.display(), err));
+                invalid += 1;
+            }
         }
-    };
+    }
 
-    let time = start.elapsed();
-    let _now = Local::now().format("%Y-%m-%d %H:%M:%S");
-
-    let mut test_report = TestReport::new(test_file_path.clone(), "Android".to_string());
-    test_report.steps_executed = steps_count;
-    test_report.execution_time = time;
-    test_report.details = report;
-
-    let report_name = test_report.save().unwrap_or_else(|e| {
-        eprintln!(
-            "{} Error writing report file: {}",
-            error_tag(),
-            e.to_string().red()
-        );
-        process::exit(1);
-    });
-
-    println!("\n\n{}", "Test suite runned successfully".green());
-    println!("    Report file: {}", report_name);
-    println!("    Actions executed: {}", steps_count);
-    println!("    Total time elapsed: {:.2} seconds", time.as_secs_f64());
-    Ok(())
+    if invalid > 0 {
+        return Err(RustPilotError::Config(format!(
+            "{} invalid file(s)",
+            invalid
+        )));
+    }
+    Ok(true)
 }
 
-fn display_startup_info() {
-    // Reset indentation level for startup info
-    rust_pilot::common::set_current_indent_level(0);
-    println!();
-    println!("{}", LOGO.yellow());
-    Logger::info(format!("rust_pilot version: {}", env!("CARGO_PKG_VERSION")));
-    println!();
+/// Prints the flattened steps of a test file
+async fn list(test_file: &Path) -> Result<bool, RustPilotError> {
+    // The listing goes to stdout on its own, flow loading logs to stderr
+    Logger::reserve_stdout(true);
+    let (header, steps, _) = load_flow(test_file, &Env::new()).await?;
+    if let Some(tags) = header.tags.filter(|tags| !tags.is_empty()) {
+        println!("Tags: {}", tags.join(", "));
+    }
+    for (index, FlowStep { step, source, .. }) in steps.iter().enumerate() {
+        match source {
+            Some(source) => println!(
+                "{:>3}. [{}] {}  ({}:{})",
+                index + 1,
+                step.kind(),
+                step.description(),
+                source.file,
+                source.line
+            ),
+            None => println!("{:>3}. [{}] {}", index + 1, step.kind(), step.description()),
+        }
+    }
+    Ok(true)
+}
+
+/// Prints the Mermaid graph of the `runFlow` tree
+async fn graph(test_file: &Path) -> Result<bool, RustPilotError> {
+    Logger::reserve_stdout(true);
+    let (_, _, flow_graph) = load_flow(test_file, &Env::new()).await?;
+    println!("graph TD");
+    println!("{}", flow_graph.trim_end());
+    Ok(true)
+}
+
+/// Renders a saved JSON report in the requested formats
+fn report(json_report: &Path, output: &ReportArgs) -> Result<bool, RustPilotError> {
+    let content = get_content(json_report)?;
+    let mut run: RunResult = serde_json::from_str(&content)
+        .map_err(|e| RustPilotError::parse(json_report.display().to_string(), e))?;
+    let json_dir = json_report.parent().unwrap_or(Path::new(""));
+    if json_dir != Path::new(output.report_dir()) {
+        run.rebase_artifacts(json_dir);
+    }
+    for report_name in publish_run(&run, &output.report_formats()?, output.report_dir(), false)? {
+        Logger::print(format!("Report file: {}", report_name));
+    }
+    Ok(true)
 }
 
-fn parse_command_line_args() -> Result<(String, String), Box<dyn std::error::Error>> {
-    let args: Vec<String> = env::args().collect();
+/// Checks the Appium server, the caps file and the platform tools
+///
+/// Every problem is logged; the last one is returned so it sets the exit code.
+async fn doctor(caps_file: Option<&Path>, server: &ServerArgs) -> Result<bool, RustPilotError> {
+    let mut problems = Vec::new();
+
+    let mut capabilities = HashMap::new();
+    if let Some(caps_file) = caps_file {
+        match check_caps(&caps_file.display().to_string()) {
+            Ok(config) => {
+                Logger::success(format!("Caps file {} is valid", caps_file.display()));
+                if let Some(tool) = platform_tool(&config.platform_name) {
+                    if tool_available(tool) {
+                        Logger::success(format!("{} is available", tool));
+                    } else {
+                        Logger::warning(format!(
+                            "{} was not found on the PATH, devices can't be listed",
+                            tool
+                        ));
+                    }
+                }
+                capabilities = config.capabilities;
+            }
+            Err(err) => problems.push(err),
+        }
+    }
+
+    match ServerOptions::resolve(&server.overrides(), &capabilities, env_var) {
+        Ok(server) => {
+            let endpoint = server.endpoint();
+            match check_appium(&endpoint).await {
+                Ok(()) => Logger::success(format!("Appium server is up at {}", endpoint)),
+                Err(err) => problems.push(err),
+            }
+        }
+        Err(err) => problems.push(err),
+    }
 
-    if args.len() < 3 {
-        Logger::error("Missing arguments");
-        Logger::error(USAGE);
-        process::exit(1);
+    match problems.pop() {
+        Some(last) => {
+            for problem in &problems {
+                Logger::error(problem);
+            }
+            Err(last)
+        }
+        None => Ok(true),
     }
+}
+
+/// Reads an environment variable, treating non UTF-8 values as unset
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
 
-    Ok((args[1].clone(), args[2].clone()))
+fn display_startup_info() {
+    Logger::print("");
+    Logger::print(LOGO.yellow());
+    Logger::info(format!("rust_pilot version: {}", env!("CARGO_PKG_VERSION")));
+    Logger::print("");
+}
+
+/// Writes the run in every requested format, returning the report paths
+///
+/// With `json_stdout` the JSON report is also printed, alone on stdout.
+fn publish_run(
+    run: &RunResult,
+    formats: &[ReportFormat],
+    report_dir: &str,
+    json_stdout: bool,
+) -> Result<Vec<String>, RustPilotError> {
+    if json_stdout {
+        println!("{}", render_report(run, &JsonRenderer)?);
+    }
+    formats
+        .iter()
+        .map(|format| save_report(run, format.renderer().as_ref(), report_dir))
+        .collect()
 }
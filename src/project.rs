@@ -0,0 +1,178 @@
+//! Project config file (`rust_pilot.toml`) with named profiles
+//!
+//! ```toml
+//! default_profile = "emulator-api30"
+//!
+//! [defaults]
+//! report = ["md", "junit"]
+//!
+//! [profiles.emulator-api30]
+//! caps = "caps/emulator.json"
+//! appium_url = "http://localhost:4723"
+//! capabilities = { "appium:avd" = "Pixel_API_30" }
+//! ```
+//!
+//! A profile is `[defaults]` overlaid with `[profiles.<name>]`. Its capabilities
+//! override the caps file; env vars and CLI flags override the profile. The
+//! `secrets` of both are kept.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::common::{
+    get_content, APPIUM_URL_CAP, BASE_PATH_CAP, CONNECT_RETRIES_CAP, CONNECT_TIMEOUT_CAP,
+    NEW_COMMAND_TIMEOUT_CAP,
+};
+use crate::error::{Result, RustPilotError};
+
+/// Name of the project config file
+pub const PROJECT_FILE_NAME: &str = "rust_pilot.toml";
+/// Environment variable selecting a profile when `--profile` isn't given
+pub const PROFILE_ENV: &str = "RUST_PILOT_PROFILE";
+
+/// Settings a profile can set; every one is optional
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileSettings {
+    /// Caps file, relative to the project file
+    pub caps: Option<PathBuf>,
+    /// Capabilities merged over the caps file
+    #[serde(default)]
+    pub capabilities: HashMap<String, Value>,
+    pub appium_url: Option<String>,
+    pub appium_base_path: Option<String>,
+    /// Seconds
+    pub connect_timeout: Option<u64>,
+    /// Seconds
+    pub new_command_timeout: Option<u64>,
+    pub connect_retries: Option<u32>,
+    pub report: Option<Vec<String>>,
+    pub report_dir: Option<String>,
+    /// Seconds
+    pub timeout: Option<u64>,
+    /// Milliseconds
+    pub poll_interval: Option<u64>,
+    pub retries: Option<u32>,
+    /// Screenshot every passing step as well
+    pub capture_steps: Option<bool>,
+    /// Variables whose values are masked in logs and reports
+    #[serde(default)]
+    pub secrets: Vec<String>,
+}
+
+impl ProfileSettings {
+    /// `self` with every setting of `other` laid over it
+    pub fn merge(mut self, other: ProfileSettings) -> Self {
+        self.capabilities.extend(other.capabilities);
+        self.secrets.extend(other.secrets);
+        ProfileSettings {
+            caps: other.caps.or(self.caps),
+            capabilities: self.capabilities,
+            appium_url: other.appium_url.or(self.appium_url),
+            appium_base_path: other.appium_base_path.or(self.appium_base_path),
+            connect_timeout: other.connect_timeout.or(self.connect_timeout),
+            new_command_timeout: other.new_command_timeout.or(self.new_command_timeout),
+            connect_retries: other.connect_retries.or(self.connect_retries),
+            report: other.report.or(self.report),
+            report_dir: other.report_dir.or(self.report_dir),
+            timeout: other.timeout.or(self.timeout),
+            poll_interval: other.poll_interval.or(self.poll_interval),
+            retries: other.retries.or(self.retries),
+            capture_steps: other.capture_steps.or(self.capture_steps),
+            secrets: self.secrets,
+        }
+    }
+
+    /// Capabilities to merge over the caps file, server settings included
+    ///
+    /// Server settings become `rustPilot:` caps so env vars and CLI flags still win over them.
+    pub fn capabilities(&self) -> HashMap<String, Value> {
+        let mut capabilities = self.capabilities.clone();
+        let settings = [
+            (APPIUM_URL_CAP, self.appium_url.clone().map(Value::from)),
+            (
+                BASE_PATH_CAP,
+                self.appium_base_path.clone().map(Value::from),
+            ),
+            (CONNECT_TIMEOUT_CAP, self.connect_timeout.map(Value::from)),
+            (
+                NEW_COMMAND_TIMEOUT_CAP,
+                self.new_command_timeout.map(Value::from),
+            ),
+            (CONNECT_RETRIES_CAP, self.connect_retries.map(Value::from)),
+        ];
+        for (key, value) in settings {
+            if let Some(value) = value {
+                capabilities.insert(key.to_string(), value);
+            }
+        }
+        capabilities
+    }
+}
+
+/// Contents of a `rust_pilot.toml`
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    /// Profile used when none is asked for
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub defaults: ProfileSettings,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, ProfileSettings>,
+    /// Where the file was read from
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl ProjectConfig {
+    /// Looks for a project file in `start` and each of its parent directories
+    pub fn discover(start: &Path) -> Result<Option<Self>> {
+        for dir in start.ancestors() {
+            let path = dir.join(PROJECT_FILE_NAME);
+            if path.is_file() {
+                return ProjectConfig::from_file(&path).map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = get_content(path)?;
+        ProjectConfig::parse(path, &contents)
+    }
+
+    pub fn parse(path: &Path, contents: &str) -> Result<Self> {
+        let mut project: ProjectConfig = toml::from_str(contents)
+            .map_err(|e| RustPilotError::parse(path.display().to_string(), e))?;
+        project.path = path.to_path_buf();
+        Ok(project)
+    }
+
+    /// Settings of the profile `name`, or of the default profile, on top of `[defaults]`
+    ///
+    /// The caps path comes back relative to the current directory.
+    pub fn profile(&self, name: Option<&str>) -> Result<ProfileSettings> {
+        let name = name.or(self.default_profile.as_deref());
+        let mut settings = match name {
+            Some(name) => {
+                let profile = self.profiles.get(name).ok_or_else(|| {
+                    RustPilotError::Config(format!(
+                        "Unknown profile {} in {} (available: {})",
+                        name,
+                        self.path.display(),
+                        self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                    ))
+                })?;
+                self.defaults.clone().merge(profile.clone())
+            }
+            None => self.defaults.clone(),
+        };
+        let base_dir = self.path.parent().unwrap_or(Path::new(""));
+        settings.caps = settings.caps.map(|caps| base_dir.join(caps));
+        Ok(settings)
+    }
+}
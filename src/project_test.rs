@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use serde_json::json;
+
+    use crate::common::{ServerOptions, ServerOverrides};
+    use crate::config::Config;
+    use crate::error::RustPilotError;
+    use crate::project::{ProjectConfig, PROJECT_FILE_NAME};
+
+    const PROJECT: &str = r#"
+default_profile = "emulator-api30"
+
+[defaults]
+report = ["md", "junit"]
+timeout = 10
+poll_interval = 500
+capabilities = { "appium:noReset" = true }
+secrets = ["PASSWORD"]
+
+[profiles.emulator-api30]
+caps = "caps/emulator.json"
+appium_url = "http://localhost:4723"
+
+[profiles.staging-pixel]
+caps = "caps/pixel.json"
+appium_url = "http://device-farm:4444"
+appium_base_path = "/wd/hub"
+timeout = 30
+capabilities = { "appium:udid" = "PIXEL7", "appium:noReset" = false }
+secrets = ["API_TOKEN"]
+"#;
+
+    fn project() -> ProjectConfig {
+        ProjectConfig::parse(Path::new("/repo/rust_pilot.toml"), PROJECT)
+            .expect("project file should parse")
+    }
+
+    #[test]
+    fn test_default_profile_is_merged_with_defaults() {
+        let profile = project().profile(None).unwrap();
+        assert_eq!(
+            profile.caps,
+            Some(PathBuf::from("/repo/caps/emulator.json"))
+        );
+        assert_eq!(
+            profile.report,
+            Some(vec!["md".to_string(), "junit".to_string()])
+        );
+        assert_eq!(profile.timeout, Some(10));
+        assert_eq!(profile.poll_interval, Some(500));
+        assert_eq!(
+            profile.capabilities.get("appium:noReset"),
+            Some(&json!(true))
+        );
+    }
+
+    #[test]
+    fn test_named_profile_overrides_defaults() {
+        let profile = project().profile(Some("staging-pixel")).unwrap();
+        assert_eq!(profile.caps, Some(PathBuf::from("/repo/caps/pixel.json")));
+        assert_eq!(profile.timeout, Some(30));
+        // Secrets of the defaults and the profile are both kept
+        assert_eq!(profile.secrets, vec!["PASSWORD", "API_TOKEN"]);
+
+        let capabilities = profile.capabilities();
+        assert_eq!(capabilities.get("appium:noReset"), Some(&json!(false)));
+        assert_eq!(capabilities.get("appium:udid"), Some(&json!("PIXEL7")));
+        assert_eq!(
+            capabilities.get("rustPilot:appiumUrl"),
+            Some(&json!("http://device-farm:4444"))
+        );
+    }
+
+    #[test]
+    fn test_profile_layers_between_caps_file_and_env() {
+        let profile = project().profile(Some("staging-pixel")).unwrap();
+        let config = Config::parse(
+            "caps.json",
+            r#"{"platformName": "Android", "appium:udid": "EMULATOR", "rustPilot:basePath": "/"}"#,
+        )
+        .unwrap()
+        .merge_capabilities(profile.capabilities())
+        .unwrap();
+        assert_eq!(
+            config.capabilities.get("appium:udid"),
+            Some(&json!("PIXEL7"))
+        );
+
+        let env = |name: &str| {
+            (name == "RUST_PILOT_APPIUM_URL").then(|| "http://env-host:4723".to_string())
+        };
+        let server =
+            ServerOptions::resolve(&ServerOverrides::default(), &config.capabilities, env).unwrap();
+        assert_eq!(server.endpoint(), "http://env-host:4723/wd/hub/");
+    }
+
+    #[test]
+    fn test_unknown_profile_and_fields_are_errors() {
+        assert!(matches!(
+            project().profile(Some("tablet")),
+            Err(RustPilotError::Config(message))
+                if message.contains("tablet") && message.contains("staging-pixel")
+        ));
+        assert!(matches!(
+            ProjectConfig::parse(Path::new("rust_pilot.toml"), "[defaults]\nreports = 1\n"),
+            Err(RustPilotError::Parse { .. })
+        ));
+    }
+
+    #[test]
+    fn test_discover_searches_parent_directories() {
+        let root = std::env::temp_dir().join("rust_pilot_project_discovery");
+        let flows = root.join("flows").join("login");
+        fs::create_dir_all(&flows).expect("Failed to create test directories");
+        fs::write(root.join(PROJECT_FILE_NAME), PROJECT).expect("Failed to write project file");
+
+        let found = ProjectConfig::discover(&flows);
+        fs::remove_dir_all(&root).expect("Failed to clean up test directories");
+
+        let project = found.unwrap().expect("project file should be found");
+        assert_eq!(project.path, root.join(PROJECT_FILE_NAME));
+        assert_eq!(
+            project.profile(None).unwrap().caps,
+            Some(root.join("caps/emulator.json"))
+        );
+    }
+}
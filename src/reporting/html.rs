@@ -0,0 +1,248 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+
+use crate::error::Result;
+use crate::reporting::junit::escape;
+use crate::reporting::{ReportRenderer, RunResult, RunStatus, StepResult, StepStatus};
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Roboto, sans-serif; margin: 2rem auto; max-width: 1100px; color: #1f2328; }
+h1 { margin-bottom: 0.25rem; }
+.summary { display: flex; flex-wrap: wrap; gap: 1.5rem; margin: 1rem 0 2rem; }
+.summary div { background: #f6f8fa; border-radius: 6px; padding: 0.6rem 1rem; }
+.badge { border-radius: 4px; color: #fff; font-weight: 600; padding: 0.1rem 0.5rem; }
+.passed .badge, .badge.passed { background: #1a7f37; }
+.failed .badge, .badge.failed, .badge.error { background: #cf222e; }
+.skipped .badge, .badge.skipped { background: #6e7781; }
+ol.timeline { list-style: none; padding: 0; }
+ol.timeline li { border-left: 4px solid #d0d7de; margin-bottom: 0.5rem; padding: 0.5rem 1rem; }
+ol.timeline li.passed { border-color: #1a7f37; background: #f0fff4; }
+ol.timeline li.failed { border-color: #cf222e; background: #fff5f5; }
+ol.timeline li.skipped { border-color: #6e7781; background: #f6f8fa; color: #57606a; }
+.duration { color: #57606a; float: right; }
+.bar { background: #0969da; height: 3px; margin-top: 0.4rem; }
+.source { color: #57606a; font-family: monospace; font-size: 0.85rem; }
+.message { font-family: monospace; white-space: pre-wrap; margin-top: 0.4rem; }
+img.screenshot { border: 1px solid #d0d7de; display: block; margin-top: 0.5rem; max-height: 480px; }
+ul.flow { border-left: 1px dashed #d0d7de; list-style: none; padding-left: 1.2rem; }
+ul.flow .flow-file { font-weight: 600; }
+pre.error { background: #fff5f5; border: 1px solid #cf222e; padding: 1rem; white-space: pre-wrap; }
+"#;
+
+/// Single-file HTML report: summary, step timeline and `runFlow` tree
+///
+/// Screenshots are read when the report is rendered and inlined as base64
+/// data URIs, so the file can be attached to a ticket on its own.
+pub struct HtmlRenderer;
+
+impl ReportRenderer for HtmlRenderer {
+    fn extension(&self) -> &'static str {
+        "html"
+    }
+
+    fn render(&self, run: &RunResult) -> Result<String> {
+        self.render_in(run, Path::new("."))
+    }
+
+    fn render_in(&self, run: &RunResult, dir: &Path) -> Result<String> {
+        let status = match run.status {
+            RunStatus::Passed => "passed",
+            RunStatus::Failed => "failed",
+            RunStatus::Error => "error",
+        };
+        let title = if run.flows.is_empty() {
+            "Test suite report".to_string()
+        } else {
+            format!("Test suite report: {}", run.flows.join(", "))
+        };
+
+        let mut html =
+            String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{}</title>\n", escape(&title)));
+        html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", STYLE));
+        html.push_str(&format!("<h1>{}</h1>\n", escape(&title)));
+
+        html.push_str("<div class=\"summary\">\n");
+        html.push_str(&format!(
+            "<div>Status <span class=\"badge {}\">{}</span></div>\n",
+            status,
+            status.to_uppercase()
+        ));
+        html.push_str(&format!("<div>Platform: {}</div>\n", escape(&run.platform)));
+        if let Some(device) = &run.device {
+            html.push_str(&format!("<div>Device: {}</div>\n", escape(device)));
+        }
+        html.push_str(&format!(
+            "<div>🕒 {}</div>\n",
+            run.started_at.format("%Y-%m-%d %H:%M:%S")
+        ));
+        html.push_str(&format!(
+            "<div>⏱️ {:.2} seconds</div>\n",
+            run.duration.as_secs_f64()
+        ));
+        html.push_str(&format!(
+            "<div>✅ {} passed · ❌ {} failed · ⏭️ {} skipped</div>\n",
+            run.passed_count(),
+            run.count(StepStatus::Failed),
+            run.count(StepStatus::Skipped)
+        ));
+        html.push_str("</div>\n");
+
+        if let Some(error) = &run.error {
+            html.push_str(&format!(
+                "<h2>Error running {} test</h2>\n<pre class=\"error\">{}</pre>\n",
+                escape(&run.platform),
+                escape(error)
+            ));
+        }
+
+        if !run.steps.is_empty() {
+            html.push_str("<h2>Steps</h2>\n<ol class=\"timeline\">\n");
+            let longest = run
+                .steps
+                .iter()
+                .map(|step| step.duration.as_secs_f64())
+                .fold(0.0, f64::max);
+            for (index, step) in run.steps.iter().enumerate() {
+                html.push_str(&step_item(index + 1, step, longest, dir));
+            }
+            html.push_str("</ol>\n");
+        }
+
+        if let Some(graph) = &run.flow_graph {
+            let tree = flow_tree(graph);
+            if !tree.is_empty() {
+                html.push_str("<h2>Flow</h2>\n");
+                html.push_str(&tree);
+                html.push_str(&format!(
+                    "<details><summary>Mermaid source</summary><pre>graph TD\n{}</pre></details>\n",
+                    escape(graph)
+                ));
+            }
+        }
+
+        html.push_str("</body>\n</html>\n");
+        Ok(html)
+    }
+}
+
+fn step_item(number: usize, step: &StepResult, longest: f64, dir: &Path) -> String {
+    let class = match step.status {
+        StepStatus::Passed => "passed",
+        StepStatus::Failed => "failed",
+        StepStatus::Skipped => "skipped",
+    };
+    let mut item = format!(
+        "<li class=\"{}\"><span class=\"badge\">{}</span> {}. {}",
+        class,
+        class.to_uppercase(),
+        number,
+        escape(&step.description)
+    );
+    if step.status != StepStatus::Skipped {
+        let seconds = step.duration.as_secs_f64();
+        item.push_str(&format!("<span class=\"duration\">{:.2}s</span>", seconds));
+        if longest > 0.0 {
+            item.push_str(&format!(
+                "<div class=\"bar\" style=\"width: {:.1}%\"></div>",
+                seconds / longest * 100.0
+            ));
+        }
+    }
+    if let Some(source) = &step.source {
+        item.push_str(&format!(
+            "<div class=\"source\">{}</div>",
+            escape(&source.to_string())
+        ));
+    }
+    if let Some(message) = &step.message {
+        item.push_str(&format!("<div class=\"message\">{}</div>", escape(message)));
+    }
+    for screenshot in step.screenshots() {
+        item.push_str(&screenshot_html(dir, screenshot));
+    }
+    item.push_str("</li>\n");
+    item
+}
+
+/// Inlines the screenshot at `path` inside `dir` as a data URI, or links it when it can't be read
+fn screenshot_html(dir: &Path, path: &str) -> String {
+    match fs::read(dir.join(path)) {
+        Ok(bytes) => format!(
+            "<img class=\"screenshot\" alt=\"{}\" src=\"data:image/png;base64,{}\">",
+            escape(path),
+            BASE64.encode(bytes)
+        ),
+        Err(_) => format!(
+            "<div><a href=\"{}\">Screenshot: {}</a></div>",
+            escape(path),
+            escape(path)
+        ),
+    }
+}
+
+/// Renders the Mermaid edges produced by `flatten_steps` as nested lists
+fn flow_tree(graph: &str) -> String {
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut has_parent = HashSet::new();
+    let mut nodes = Vec::new();
+    for line in graph.lines() {
+        let Some((parent, child)) = line.split_once("-->") else {
+            continue;
+        };
+        let (parent, child) = (parent.trim(), child.trim());
+        for node in [parent, child] {
+            if !nodes.contains(&node) {
+                nodes.push(node);
+            }
+        }
+        children.entry(parent).or_default().push(child);
+        has_parent.insert(child);
+    }
+
+    let mut html = String::new();
+    let mut visited = HashSet::new();
+    for root in nodes.iter().filter(|node| !has_parent.contains(*node)) {
+        html.push_str("<ul class=\"flow\">\n");
+        render_node(root, &children, &mut visited, &mut html);
+        html.push_str("</ul>\n");
+    }
+    html
+}
+
+fn render_node<'a>(
+    node: &'a str,
+    children: &HashMap<&'a str, Vec<&'a str>>,
+    visited: &mut HashSet<&'a str>,
+    html: &mut String,
+) {
+    if !visited.insert(node) {
+        return;
+    }
+    let label = node_label(node);
+    match children.get(node) {
+        Some(nested) => {
+            html.push_str(&format!(
+                "<li><span class=\"flow-file\">{}</span>\n<ul class=\"flow\">\n",
+                escape(label)
+            ));
+            for child in nested {
+                render_node(child, children, visited, html);
+            }
+            html.push_str("</ul></li>\n");
+        }
+        None => html.push_str(&format!("<li>{}</li>\n", escape(label))),
+    }
+}
+
+/// Text between the parentheses of a Mermaid node such as `idRunFlow3(login.yml)`
+fn node_label(node: &str) -> &str {
+    match (node.find('('), node.rfind(')')) {
+        (Some(start), Some(end)) if start < end => &node[start + 1..end],
+        _ => node,
+    }
+}
@@ -0,0 +1,71 @@
+//! Versioned JSON report
+//!
+//! The document is a [`RunResult`] with a `schema_version` field added at the
+//! top level. Consumers should check `schema_version` first: fields are only
+//! ever added within a version; renames and removals bump it.
+//!
+//! Schema version 1:
+//!
+//! ```text
+//! {
+//!   "schema_version": 1,
+//!   "flows": [string],              top-level test files that were run
+//!   "platform": string,            "Android", "iOS" or "Flutter"
+//!   "device": string | null,       device name or udid from the caps file
+//!   "status": "passed" | "failed" | "error",
+//!   "error": string | null,        why the run could not start (status "error")
+//!   "started_at": string,          RFC 3339 timestamp
+//!   "duration_ms": integer,
+//!   "flow_graph": string | null,   Mermaid edges of the runFlow tree
+//!   "steps": [
+//!     {
+//!       "kind": string,            Maestro command: "tapOn", "assertVisible", ...
+//!       "description": string,
+//!       "selector": object | null, e.g. {"text": "Login"}, {"id": "submit", "index": 1}
+//!       "source": {                where the step was declared, or null
+//!         "file": string, "line": integer, "column": integer,
+//!         "include_chain": [string],   flow files from the top-level one down to file
+//!         "depth": integer             runFlow nesting, 0 for the top-level flow
+//!       } | null,
+//!       "flow": string | null,     top-level test file the step was run from
+//!       "status": "passed" | "failed" | "skipped",
+//!       "message": string | null,  failure message or skip reason
+//!       "started_at": string | null,
+//!       "duration_ms": integer,
+//!       "artifacts": [{"kind": "screenshot", "path": string}]  paths relative to the report directory
+//!     }
+//!   ]
+//! }
+//! ```
+
+use serde::Serialize;
+
+use crate::error::{Result, RustPilotError};
+use crate::reporting::{ReportRenderer, RunResult};
+
+/// Version of the JSON report schema documented above
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    run: &'a RunResult,
+}
+
+/// Pretty-printed serialization of the [`RunResult`] model
+pub struct JsonRenderer;
+
+impl ReportRenderer for JsonRenderer {
+    fn extension(&self) -> &'static str {
+        "json"
+    }
+
+    fn render(&self, run: &RunResult) -> Result<String> {
+        let report = JsonReport {
+            schema_version: JSON_SCHEMA_VERSION,
+            run,
+        };
+        serde_json::to_string_pretty(&report).map_err(|e| RustPilotError::parse("JSON report", e))
+    }
+}
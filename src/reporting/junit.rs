@@ -0,0 +1,187 @@
+use crate::error::Result;
+use crate::reporting::{ReportRenderer, RunResult, RunStatus, StepResult, StepStatus};
+
+/// JUnit XML, as ingested by most CI dashboards
+///
+/// Every top-level test file becomes one `<testsuite>` and every step one
+/// `<testcase>`. Screenshots are attached through the `[[ATTACHMENT|path]]`
+/// convention understood by Jenkins and GitLab.
+pub struct JunitRenderer;
+
+impl ReportRenderer for JunitRenderer {
+    fn extension(&self) -> &'static str {
+        "xml"
+    }
+
+    fn render(&self, run: &RunResult) -> Result<String> {
+        let errors = usize::from(run.status == RunStatus::Error);
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuites name=\"rust_pilot\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+            run.steps.len() + errors,
+            run.count(StepStatus::Failed),
+            errors,
+            run.count(StepStatus::Skipped),
+            run.duration.as_secs_f64()
+        ));
+
+        let suites = suites(run);
+        for (index, (name, steps)) in suites.iter().enumerate() {
+            // A run that could not start stopped at its last flow
+            let error = run.error.as_ref().filter(|_| index + 1 == suites.len());
+            render_suite(&mut xml, run, name, steps, error);
+        }
+
+        xml.push_str("</testsuites>\n");
+        Ok(xml)
+    }
+}
+
+/// Steps of `run` grouped by top-level test file, in run order
+///
+/// Steps that don't record their flow, as in reports of older versions, stay
+/// with the flow of the step before them.
+fn suites(run: &RunResult) -> Vec<(String, Vec<&StepResult>)> {
+    let mut suites: Vec<(String, Vec<&StepResult>)> = run
+        .flows
+        .iter()
+        .map(|flow| (flow.clone(), Vec::new()))
+        .collect();
+    if suites.is_empty() {
+        suites.push((run.platform.clone(), Vec::new()));
+    }
+    let mut current = 0;
+    for step in &run.steps {
+        let flow = step.flow.as_ref().or_else(|| {
+            step.source
+                .as_ref()
+                .and_then(|source| source.include_chain.first())
+        });
+        if let Some(flow) = flow {
+            current = match suites.iter().position(|(name, _)| name == flow) {
+                Some(position) => position,
+                None => {
+                    suites.push((flow.clone(), Vec::new()));
+                    suites.len() - 1
+                }
+            };
+        }
+        suites[current].1.push(step);
+    }
+    suites
+}
+
+fn render_suite(
+    xml: &mut String,
+    run: &RunResult,
+    name: &str,
+    steps: &[&StepResult],
+    error: Option<&String>,
+) {
+    let errors = usize::from(error.is_some());
+    let count = |status| steps.iter().filter(|step| step.status == status).count();
+    let time: f64 = steps.iter().map(|step| step.duration.as_secs_f64()).sum();
+    let started_at = steps
+        .iter()
+        .find_map(|step| step.started_at)
+        .unwrap_or(run.started_at);
+    xml.push_str(&format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" skipped=\"{}\" time=\"{:.3}\" timestamp=\"{}\">\n",
+        escape(name),
+        steps.len() + errors,
+        count(StepStatus::Failed),
+        errors,
+        count(StepStatus::Skipped),
+        time,
+        started_at.format("%Y-%m-%dT%H:%M:%S")
+    ));
+
+    xml.push_str("    <properties>\n");
+    xml.push_str(&property("platform", &run.platform));
+    if let Some(device) = &run.device {
+        xml.push_str(&property("device", device));
+    }
+    xml.push_str("    </properties>\n");
+
+    if let Some(error) = error {
+        xml.push_str(&format!(
+            "    <testcase name=\"Launch app\" classname=\"{}\" time=\"0.000\">\n",
+            escape(name)
+        ));
+        xml.push_str(&format!(
+            "      <error message=\"{}\">{}</error>\n",
+            escape(error),
+            escape(error)
+        ));
+        xml.push_str("    </testcase>\n");
+    }
+
+    for step in steps {
+        xml.push_str(&format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(&step.description),
+            escape(name),
+            step.duration.as_secs_f64()
+        ));
+        if let Some(source) = &step.source {
+            xml.push_str(&format!(
+                " file=\"{}\" line=\"{}\"",
+                escape(&source.file),
+                source.line
+            ));
+        }
+        let message = step.message.as_deref().unwrap_or_default();
+        match step.status {
+            StepStatus::Passed => xml.push_str("/>\n"),
+            StepStatus::Skipped => {
+                xml.push_str(">\n");
+                xml.push_str(&format!(
+                    "      <skipped message=\"{}\"/>\n",
+                    escape(message)
+                ));
+                xml.push_str("    </testcase>\n");
+            }
+            StepStatus::Failed => {
+                xml.push_str(">\n");
+                xml.push_str(&format!(
+                    "      <failure message=\"{}\">{}</failure>\n",
+                    escape(message),
+                    escape(message)
+                ));
+                for screenshot in step.screenshots() {
+                    xml.push_str(&format!(
+                        "      <system-out>[[ATTACHMENT|{}]]</system-out>\n",
+                        escape(screenshot)
+                    ));
+                }
+                xml.push_str("    </testcase>\n");
+            }
+        }
+    }
+
+    xml.push_str("  </testsuite>\n");
+}
+
+fn property(name: &str, value: &str) -> String {
+    format!(
+        "      <property name=\"{}\" value=\"{}\"/>\n",
+        name,
+        escape(value)
+    )
+}
+
+/// Escapes text for use in XML attributes and character data
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
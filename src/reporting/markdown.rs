@@ -0,0 +1,86 @@
+use crate::common::tags::{invalid_report_tag, skipped_report_tag, valid_report_tag};
+use crate::error::Result;
+use crate::reporting::{ReportRenderer, RunResult, RunStatus, StepResult, StepStatus};
+
+/// The historical `REPORT_*.md` format
+pub struct MarkdownRenderer;
+
+impl ReportRenderer for MarkdownRenderer {
+    fn extension(&self) -> &'static str {
+        "md"
+    }
+
+    fn render(&self, run: &RunResult) -> Result<String> {
+        let details = match (&run.status, &run.error) {
+            (RunStatus::Error, Some(error)) => format!(
+                "### ERROR RUNNING {} TEST\n```{}```",
+                run.platform.to_uppercase(),
+                error
+            ),
+            _ => steps_markdown_table(&run.platform, &run.steps),
+        };
+        let flow = match &run.flow_graph {
+            Some(graph) if !graph.is_empty() => {
+                format!("\n## Flow\n\n```mermaid\ngraph TD\n{}```\n", graph)
+            }
+            _ => String::new(),
+        };
+        Ok(format!(
+            "# Test suite report\n\n\
+            Test file: {}\n\n\
+            Platform: {}\n\n\
+            🕒 Date and time: {}\n\n\
+            ✅ Steps executed: {} successfully\n\n\
+            ⏱️ Total execution time: {:.2} seconds\n\n\
+            ## Test Details\n\n\
+            {}\n{}",
+            run.flows.join(", "),
+            run.platform,
+            run.started_at.format("%Y-%m-%d %H:%M:%S"),
+            run.passed_count(),
+            run.duration.as_secs_f64(),
+            details,
+            flow
+        ))
+    }
+}
+
+/// Renders step results as the Markdown table used in the report details
+pub fn steps_markdown_table(platform: &str, results: &[StepResult]) -> String {
+    let mut table = format!(
+        "### {} Steps\n| Description | State | Observation | Source |\n |----|----|----|----|\n",
+        platform
+    );
+    for result in results {
+        let state = match result.status {
+            StepStatus::Passed => valid_report_tag(),
+            StepStatus::Failed => invalid_report_tag(),
+            StepStatus::Skipped => skipped_report_tag(),
+        };
+        let mut observation = match result.status {
+            StepStatus::Skipped => String::new(),
+            _ => format!("{:.2}s", result.duration.as_secs_f64()),
+        };
+        if let Some(message) = &result.message {
+            if !observation.is_empty() {
+                observation.push_str(" - ");
+            }
+            observation.push_str(&message.replace('|', "\\|").replace('\n', " "));
+        }
+        for screenshot in result.screenshots() {
+            observation.push_str(&format!(" [screenshot]({})", screenshot));
+        }
+        let source = match &result.source {
+            Some(source) => format!("`{}`", source.to_string().replace('|', "\\|")),
+            None => String::new(),
+        };
+        table.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            result.description.replace('|', "\\|"),
+            state,
+            observation,
+            source
+        ));
+    }
+    table
+}
@@ -0,0 +1,367 @@
+//! Run results and report rendering
+//!
+//! The executor produces a [`RunResult`]; every output format is a
+//! [`ReportRenderer`] over that model, so adding a format never touches step
+//! execution.
+
+mod html;
+mod json;
+mod junit;
+mod markdown;
+
+pub use html::HtmlRenderer;
+pub use json::{JsonRenderer, JSON_SCHEMA_VERSION};
+pub use junit::JunitRenderer;
+pub use markdown::{steps_markdown_table, MarkdownRenderer};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::common::driver::Selector;
+use crate::common::models::{SourceLocation, Step};
+use crate::error::{Result, RustPilotError};
+use crate::secrets;
+
+/// Directory reports are written to when none is given
+pub const DEFAULT_REPORT_DIR: &str = "reports";
+/// Directory of the report directory screenshots are written to
+pub const SCREENSHOT_DIR: &str = "screenshots";
+
+/// Outcome of a single step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Kind of file produced while running a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Screenshot,
+}
+
+/// File attached to a step result
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub kind: ArtifactKind,
+    pub path: String,
+}
+
+impl Artifact {
+    pub fn screenshot(path: String) -> Self {
+        Artifact {
+            kind: ArtifactKind::Screenshot,
+            path,
+        }
+    }
+}
+
+/// What happened when a step was executed (or why it wasn't)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepResult {
+    /// Maestro command of the step (`tapOn`, `assertVisible`, ...)
+    #[serde(default)]
+    pub kind: String,
+    pub description: String,
+    /// Element the step targets, if any
+    #[serde(default)]
+    pub selector: Option<Selector>,
+    /// Where the step was declared
+    #[serde(default)]
+    pub source: Option<SourceLocation>,
+    /// Top-level test file the step was run from
+    #[serde(default)]
+    pub flow: Option<String>,
+    pub status: StepStatus,
+    /// Failure message, or the reason the step was skipped
+    pub message: Option<String>,
+    /// When the step started; steps that never ran have none
+    #[serde(default)]
+    pub started_at: Option<DateTime<Local>>,
+    #[serde(rename = "duration_ms", with = "duration_ms")]
+    pub duration: Duration,
+    /// Files produced by the step, such as the screenshot taken on failure,
+    /// relative to the report directory
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+}
+
+impl StepResult {
+    pub fn passed(description: String, duration: Duration) -> Self {
+        StepResult::new(description, StepStatus::Passed, None, duration)
+    }
+
+    pub fn failed(
+        description: String,
+        message: String,
+        duration: Duration,
+        screenshot: Option<String>,
+    ) -> Self {
+        let mut result = StepResult::new(description, StepStatus::Failed, Some(message), duration);
+        result.artifacts = screenshot.into_iter().map(Artifact::screenshot).collect();
+        result
+    }
+
+    pub fn skipped(description: String, reason: String) -> Self {
+        StepResult::new(
+            description,
+            StepStatus::Skipped,
+            Some(reason),
+            Duration::from_secs(0),
+        )
+    }
+
+    fn new(
+        description: String,
+        status: StepStatus,
+        message: Option<String>,
+        duration: Duration,
+    ) -> Self {
+        StepResult {
+            kind: String::new(),
+            description,
+            selector: None,
+            source: None,
+            flow: None,
+            status,
+            message,
+            started_at: None,
+            duration,
+            artifacts: Vec::new(),
+        }
+    }
+
+    /// Records which step this is the result of
+    pub fn for_step(mut self, step: &Step, selector: Option<Selector>) -> Self {
+        self.kind = step.kind().to_string();
+        self.selector = selector;
+        self
+    }
+
+    /// Screenshots attached to the step
+    pub fn screenshots(&self) -> impl Iterator<Item = &str> {
+        self.artifacts
+            .iter()
+            .filter(|artifact| artifact.kind == ArtifactKind::Screenshot)
+            .map(|artifact| artifact.path.as_str())
+    }
+}
+
+/// Overall outcome of a run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Every executed step passed
+    Passed,
+    /// At least one step failed
+    Failed,
+    /// The run could not start (invalid caps, Appium unreachable, ...)
+    Error,
+}
+
+/// Everything known about one test run, independent of the output format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunResult {
+    /// Test files that were run
+    pub flows: Vec<String>,
+    pub steps: Vec<StepResult>,
+    pub started_at: DateTime<Local>,
+    #[serde(rename = "duration_ms", with = "duration_ms")]
+    pub duration: Duration,
+    pub platform: String,
+    #[serde(default)]
+    pub device: Option<String>,
+    pub status: RunStatus,
+    /// Why the run could not start, when `status` is `Error`
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Mermaid edges of the `runFlow` tree, as built by `flatten_steps`
+    #[serde(default)]
+    pub flow_graph: Option<String>,
+}
+
+impl RunResult {
+    /// A copy of the run with every registered secret masked
+    pub fn redacted(&self) -> Result<RunResult> {
+        let mut value =
+            serde_json::to_value(self).map_err(|e| RustPilotError::parse("run result", e))?;
+        secrets::mask_value(&mut value);
+        serde_json::from_value(value).map_err(|e| RustPilotError::parse("run result", e))
+    }
+
+    pub fn new(platform: String) -> Self {
+        RunResult {
+            flows: Vec::new(),
+            steps: Vec::new(),
+            started_at: Local::now(),
+            duration: Duration::from_secs(0),
+            platform,
+            device: None,
+            status: RunStatus::Passed,
+            error: None,
+            flow_graph: None,
+        }
+    }
+
+    /// Records `flow` as the top-level test file of the run and of each of its steps
+    pub fn set_flow(&mut self, flow: String) {
+        for step in &mut self.steps {
+            step.flow = Some(flow.clone());
+        }
+        self.flows.push(flow);
+    }
+
+    /// Appends the flows and steps of `other`, run after this one
+    pub fn merge(&mut self, other: RunResult) {
+        self.flows.extend(other.flows);
+        self.steps.extend(other.steps);
+        self.duration += other.duration;
+        self.device = self.device.take().or(other.device);
+        if other.status == RunStatus::Failed && self.status == RunStatus::Passed {
+            self.status = RunStatus::Failed;
+        }
+        if let Some(graph) = other.flow_graph {
+            self.flow_graph
+                .get_or_insert_with(String::new)
+                .push_str(&graph);
+        }
+    }
+
+    /// Marks the run as unable to start
+    pub fn set_error(&mut self, error: String) {
+        self.status = RunStatus::Error;
+        self.error = Some(error);
+    }
+
+    /// Number of steps that actually ran and passed
+    pub fn passed_count(&self) -> usize {
+        self.count(StepStatus::Passed)
+    }
+
+    pub fn count(&self, status: StepStatus) -> usize {
+        self.steps
+            .iter()
+            .filter(|step| step.status == status)
+            .count()
+    }
+
+    /// Moves artifact paths, relative to the report directory `from`, to be usable from anywhere
+    pub fn rebase_artifacts(&mut self, from: &Path) {
+        for artifact in self.steps.iter_mut().flat_map(|step| &mut step.artifacts) {
+            let path = from.join(&artifact.path);
+            artifact.path = fs::canonicalize(&path)
+                .unwrap_or(path)
+                .display()
+                .to_string();
+        }
+    }
+
+    /// The step that stopped the run, if any
+    pub fn failed_step(&self) -> Option<&StepResult> {
+        self.steps
+            .iter()
+            .find(|step| step.status == StepStatus::Failed)
+    }
+}
+
+/// An output format for run results
+pub trait ReportRenderer {
+    /// File extension of the rendered report, without the dot
+    fn extension(&self) -> &'static str;
+
+    fn render(&self, run: &RunResult) -> Result<String>;
+
+    /// Renders `run` as a report written inside `dir`, which artifact paths are relative to
+    fn render_in(&self, run: &RunResult, _dir: &Path) -> Result<String> {
+        self.render(run)
+    }
+}
+
+/// Report formats selectable with `--report`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Markdown,
+    Json,
+    Junit,
+    Html,
+}
+
+impl ReportFormat {
+    /// Parses a `--report` value such as `md`, `json` or `junit`
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "md" | "markdown" => Ok(ReportFormat::Markdown),
+            "json" => Ok(ReportFormat::Json),
+            "junit" | "xml" => Ok(ReportFormat::Junit),
+            "html" => Ok(ReportFormat::Html),
+            other => Err(RustPilotError::Config(format!(
+                "Unknown report format {} (expected md, json, junit or html)",
+                other
+            ))),
+        }
+    }
+
+    pub fn renderer(&self) -> Box<dyn ReportRenderer> {
+        match self {
+            ReportFormat::Markdown => Box::new(MarkdownRenderer),
+            ReportFormat::Json => Box::new(JsonRenderer),
+            ReportFormat::Junit => Box::new(JunitRenderer),
+            ReportFormat::Html => Box::new(HtmlRenderer),
+        }
+    }
+}
+
+/// Renders `run` with every registered secret masked
+pub fn render_report(run: &RunResult, renderer: &dyn ReportRenderer) -> Result<String> {
+    renderer.render(&run.redacted()?)
+}
+
+/// Renders `run` and writes it as `REPORT_<timestamp>.<ext>` inside `dir_name`
+pub fn save_report(
+    run: &RunResult,
+    renderer: &dyn ReportRenderer,
+    dir_name: &str,
+) -> Result<String> {
+    // Ensure directory exists
+    let dir_path = Path::new(dir_name);
+    if !dir_path.exists() {
+        fs::create_dir_all(dir_path).map_err(|e| RustPilotError::io(dir_path, e))?;
+    }
+
+    let report_name = format!(
+        "{}/REPORT_{}.{}",
+        dir_name,
+        run.started_at.format("%Y%m%d_%H-%M-%S"),
+        renderer.extension()
+    );
+
+    let content = renderer.render_in(&run.redacted()?, dir_path)?;
+    let mut report_file =
+        File::create(&report_name).map_err(|e| RustPilotError::io(&report_name, e))?;
+    report_file
+        .write_all(content.as_bytes())
+        .map_err(|e| RustPilotError::io(&report_name, e))?;
+
+    Ok(report_name)
+}
+
+/// Serializes durations as whole milliseconds
+mod duration_ms {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(duration.as_millis() as u64)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
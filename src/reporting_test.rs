@@ -3,7 +3,23 @@ mod tests {
     use std::fs;
     use std::path::Path;
     use std::time::Duration;
-    use crate::reporting::TestReport;
+    use crate::reporting::{
+        render_report, save_report, steps_markdown_table, HtmlRenderer, JsonRenderer, JunitRenderer, MarkdownRenderer,
+        ReportFormat, ReportRenderer, RunResult, RunStatus, StepResult, StepStatus,
+        DEFAULT_REPORT_DIR, JSON_SCHEMA_VERSION,
+    };
+    use crate::common::executor::step_selector;
+    use crate::common::models::{ElementSelector, SourceLocation, Step};
+
+    fn sample_run(platform: &str) -> RunResult {
+        let mut run = RunResult::new(platform.to_string());
+        run.flows = vec!["test_file.yml".to_string()];
+        run.duration = Duration::from_secs(10);
+        run.steps = (0..5)
+            .map(|i| StepResult::passed(format!("Step {}", i), Duration::from_secs(2)))
+            .collect();
+        run
+    }
 
     #[test]
     fn test_report_directory_creation() {
@@ -13,17 +29,8 @@ mod tests {
             fs::remove_dir_all(reports_dir).expect("Failed to remove test_reports directory");
         }
 
-        // Create a test report
-        let report = TestReport {
-            test_file: "test_file.yml".to_string(),
-            platform: "Test".to_string(),
-            steps_executed: 5,
-            execution_time: Duration::from_secs(10),
-            details: "Test details".to_string(),
-        };
-
         // Save the report to a custom directory
-        let save_result = report.save_to_dir("test_reports");
+        let save_result = save_report(&sample_run("Test"), &MarkdownRenderer, "test_reports");
         assert!(save_result.is_ok(), "Failed to save report: {:?}", save_result.err());
 
         // Verify directory was created
@@ -31,6 +38,7 @@ mod tests {
 
         // Verify file was created
         let report_path = save_result.unwrap();
+        assert!(report_path.ends_with(".md"), "Report file has the wrong extension");
         assert!(Path::new(&report_path).exists(), "Report file was not created");
 
         // Cleanup
@@ -39,71 +47,71 @@ mod tests {
 
     #[test]
     fn test_report_content() {
-        // Create a test report with known values
-        let report = TestReport {
-            test_file: "test_file.yml".to_string(),
-            platform: "Test Platform".to_string(),
-            steps_executed: 10,
-            execution_time: Duration::from_secs(30),
-            details: "Test execution details".to_string(),
-        };
+        // Create a run with known values
+        let mut run = sample_run("Test Platform");
+        run.duration = Duration::from_secs(30);
 
         // Generate markdown content
-        let markdown = report.generate_markdown();
+        let markdown = MarkdownRenderer.render(&run).unwrap();
 
         // Verify content contains expected information
         assert!(markdown.contains("test_file.yml"), "Report doesn't contain test file name");
         assert!(markdown.contains("Test Platform"), "Report doesn't contain platform name");
-        assert!(markdown.contains("Steps executed: 10"), "Report doesn't contain correct step count");
+        assert!(markdown.contains("Steps executed: 5"), "Report doesn't contain correct step count");
         assert!(markdown.contains("30.00 seconds"), "Report doesn't contain correct execution time");
-        assert!(markdown.contains("Test execution details"), "Report doesn't contain details");
+        assert!(markdown.contains("| Step 4 | ✅ Ok | 2.00s |"), "Report doesn't contain details");
     }
-    
+
+    #[test]
+    fn test_report_content_for_run_that_could_not_start() {
+        let mut run = RunResult::new("Android".to_string());
+        run.set_error("Connection error: refused".to_string());
+
+        let markdown = MarkdownRenderer.render(&run).unwrap();
+
+        assert_eq!(run.status, RunStatus::Error);
+        assert!(markdown.contains("### ERROR RUNNING ANDROID TEST"));
+        assert!(markdown.contains("Connection error: refused"));
+    }
+
     #[test]
-    fn test_report_new() {
+    fn test_run_result_new() {
         // Test the constructor method
-        let report = TestReport::new("test_file.yml".to_string(), "Android".to_string());
-        
+        let run = RunResult::new("Android".to_string());
+
         // Verify initial values
-        assert_eq!(report.test_file, "test_file.yml");
-        assert_eq!(report.platform, "Android");
-        assert_eq!(report.steps_executed, 0);
-        assert_eq!(report.execution_time, Duration::from_secs(0));
-        assert_eq!(report.details, "");
+        assert_eq!(run.platform, "Android");
+        assert_eq!(run.status, RunStatus::Passed);
+        assert_eq!(run.passed_count(), 0);
+        assert_eq!(run.duration, Duration::from_secs(0));
+        assert!(run.flows.is_empty());
+        assert!(run.device.is_none());
+        assert!(run.failed_step().is_none());
     }
-    
+
     #[test]
     fn test_report_default_save() {
         // Setup: Ensure reports directory doesn't exist
-        let reports_dir = Path::new("reports");
+        let reports_dir = Path::new(DEFAULT_REPORT_DIR);
         if reports_dir.exists() {
             fs::remove_dir_all(reports_dir).expect("Failed to remove reports directory");
         }
-        
-        // Create a test report
-        let report = TestReport {
-            test_file: "test_file.yml".to_string(),
-            platform: "Test".to_string(),
-            steps_executed: 5,
-            execution_time: Duration::from_secs(10),
-            details: "Test details".to_string(),
-        };
-        
+
         // Save the report to the default directory
-        let save_result = report.save();
+        let save_result = save_report(&sample_run("Test"), &MarkdownRenderer, DEFAULT_REPORT_DIR);
         assert!(save_result.is_ok(), "Failed to save report to default directory: {:?}", save_result.err());
-        
+
         // Verify directory was created
         assert!(reports_dir.exists(), "Default reports directory was not created");
-        
+
         // Verify file was created
         let report_path = save_result.unwrap();
         assert!(Path::new(&report_path).exists(), "Report file was not created in default directory");
-        
+
         // Cleanup
         fs::remove_dir_all(reports_dir).expect("Failed to clean up reports directory");
     }
-    
+
     #[test]
     fn test_report_with_existing_directory() {
         // Setup: Create the directory first
@@ -111,25 +119,320 @@ mod tests {
         if !reports_dir.exists() {
             fs::create_dir(reports_dir).expect("Failed to create existing_reports directory");
         }
-        
-        // Create a test report
-        let report = TestReport {
-            test_file: "test_file.yml".to_string(),
-            platform: "Test".to_string(),
-            steps_executed: 5,
-            execution_time: Duration::from_secs(10),
-            details: "Test details".to_string(),
-        };
-        
+
         // Save the report to the existing directory
-        let save_result = report.save_to_dir("existing_reports");
+        let save_result = save_report(&sample_run("Test"), &JsonRenderer, "existing_reports");
         assert!(save_result.is_ok(), "Failed to save report to existing directory: {:?}", save_result.err());
-        
+
         // Verify file was created
         let report_path = save_result.unwrap();
+        assert!(report_path.ends_with(".json"), "Report file has the wrong extension");
         assert!(Path::new(&report_path).exists(), "Report file was not created in existing directory");
-        
+
         // Cleanup
         fs::remove_dir_all(reports_dir).expect("Failed to clean up existing_reports directory");
     }
+
+    #[test]
+    fn test_json_report_round_trip() {
+        let mut run = sample_run("iOS");
+        run.device = Some("iPhone 15".to_string());
+        run.status = RunStatus::Failed;
+        let step = Step::AssertVisible {
+            assertVisible: ElementSelector::Text("Explore".to_string()),
+        };
+        run.steps.push(
+            StepResult::failed(
+                "Assert Explore visible".to_string(),
+                "Element not found".to_string(),
+                Duration::from_millis(1250),
+                Some("error_screenshot.png".to_string()),
+            )
+            .for_step(&step, step_selector(&step)),
+        );
+
+        let json = JsonRenderer.render(&run).unwrap();
+        assert!(json.contains(&format!("\"schema_version\": {}", JSON_SCHEMA_VERSION)));
+        assert!(json.contains("\"status\": \"failed\""));
+        assert!(json.contains("\"duration_ms\": 1250"));
+        assert!(json.contains("\"kind\": \"assertVisible\""));
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let failed = &value["steps"][5];
+        assert_eq!(failed["selector"], serde_json::json!({ "text": "Explore" }));
+        assert_eq!(
+            failed["artifacts"],
+            serde_json::json!([{ "kind": "screenshot", "path": "error_screenshot.png" }])
+        );
+        assert!(failed["source"].is_null());
+
+        let parsed: RunResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.platform, "iOS");
+        assert_eq!(parsed.device.as_deref(), Some("iPhone 15"));
+        assert_eq!(parsed.status, RunStatus::Failed);
+        assert_eq!(parsed.steps.len(), 6);
+        assert_eq!(parsed.failed_step().unwrap().duration, Duration::from_millis(1250));
+    }
+
+    #[test]
+    fn test_reports_mask_secrets() {
+        crate::secrets::register_value("report-secret-31337");
+        let mut run = sample_run("Android");
+        run.steps.push(StepResult::failed(
+            "Input text report-secret-31337".to_string(),
+            "Field rejected report-secret-31337".to_string(),
+            Duration::from_millis(10),
+            None,
+        ));
+
+        for format in [ReportFormat::Markdown, ReportFormat::Json, ReportFormat::Junit] {
+            let report = render_report(&run, format.renderer().as_ref()).unwrap();
+            assert!(!report.contains("report-secret-31337"), "{:?} leaks the secret", format);
+            assert!(report.contains("Input text ***"));
+        }
+    }
+
+    #[test]
+    fn test_merge_combines_flows_of_a_suite() {
+        let mut run = sample_run("Android");
+        run.flow_graph = Some("idRoot1(a.yml) --> idStepName2(TapOn)\n".to_string());
+        let mut second = sample_run("Android");
+        second.flows = vec!["b.yml".to_string()];
+        second.device = Some("Pixel 7".to_string());
+        second.status = RunStatus::Failed;
+        second.flow_graph = Some("idRoot3(b.yml) --> idStepName4(TapOn)\n".to_string());
+
+        run.merge(second);
+        assert_eq!(run.flows, vec!["test_file.yml", "b.yml"]);
+        assert_eq!(run.steps.len(), 10);
+        assert_eq!(run.duration, Duration::from_secs(20));
+        assert_eq!(run.device.as_deref(), Some("Pixel 7"));
+        assert_eq!(run.status, RunStatus::Failed);
+        assert_eq!(
+            run.flow_graph.as_deref(),
+            Some("idRoot1(a.yml) --> idStepName2(TapOn)\nidRoot3(b.yml) --> idStepName4(TapOn)\n")
+        );
+    }
+
+    #[test]
+    fn test_step_result_constructors() {
+        let passed = StepResult::passed("Tap on CONTINUE".to_string(), Duration::from_millis(300));
+        assert_eq!(passed.status, StepStatus::Passed);
+        assert!(passed.message.is_none());
+
+        let skipped = StepResult::skipped("Tap on NEXT".to_string(), "A previous step failed".to_string());
+        assert_eq!(skipped.status, StepStatus::Skipped);
+        assert_eq!(skipped.duration, Duration::from_secs(0));
+    }
+
+    #[test]
+    fn test_steps_markdown_table_marks_failure_and_skipped_steps() {
+        let mut results = vec![
+            StepResult::passed("Tap on CONTINUE".to_string(), Duration::from_millis(1500)),
+            StepResult::failed(
+                "Assert Explore visible".to_string(),
+                "Element not found: text=Explore or description=Explore".to_string(),
+                Duration::from_secs(30),
+                Some("error_screenshot.png".to_string()),
+            ),
+            StepResult::skipped("Tap on GET STARTED".to_string(), "A previous step failed".to_string()),
+        ];
+        results[1].source = Some(SourceLocation {
+            file: "login/add-address.yml".to_string(),
+            line: 12,
+            column: 1,
+            include_chain: vec!["main.yml".to_string(), "login/add-address.yml".to_string()],
+            depth: 1,
+        });
+
+        let table = steps_markdown_table("Android", &results);
+
+        assert!(table.starts_with("### Android Steps"));
+        assert!(table.contains("| Tap on CONTINUE | ✅ Ok | 1.50s |"));
+        assert!(table.contains("| Assert Explore visible | ❌ Error | 30.00s - Element not found"));
+        assert!(table.contains(
+            "[screenshot](error_screenshot.png) | `login/add-address.yml:12:1 (via main.yml -> login/add-address.yml)` |"
+        ));
+        assert!(table.contains("| Tap on GET STARTED | ⏭️ Skipped | A previous step failed |"));
+    }
+
+    #[test]
+    fn test_junit_report_marks_failures_and_skipped_steps() {
+        let mut run = sample_run("Android");
+        run.flows = vec!["flows/login.yml".to_string()];
+        run.device = Some("Pixel <7>".to_string());
+        run.status = RunStatus::Failed;
+        run.steps.push(StepResult::failed(
+            "Assert \"Explore\" visible".to_string(),
+            "Element not found: text=Explore or description=Explore".to_string(),
+            Duration::from_millis(1250),
+            Some("error_screenshot.png".to_string()),
+        ));
+        let mut skipped = StepResult::skipped(
+            "Tap on NEXT".to_string(),
+            "A previous step failed".to_string(),
+        );
+        skipped.source = Some(SourceLocation {
+            file: "flows/login.yml".to_string(),
+            line: 9,
+            column: 1,
+            include_chain: vec!["flows/login.yml".to_string()],
+            depth: 0,
+        });
+        run.steps.push(skipped);
+
+        let xml = JunitRenderer.render(&run).unwrap();
+
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        assert!(xml.contains("<testsuite name=\"flows/login.yml\" tests=\"7\" failures=\"1\" errors=\"0\" skipped=\"1\""));
+        assert!(xml.contains("<testcase name=\"Step 0\" classname=\"flows/login.yml\" time=\"2.000\"/>"));
+        assert!(xml.contains("<testcase name=\"Assert &quot;Explore&quot; visible\" classname=\"flows/login.yml\" time=\"1.250\">"));
+        assert!(xml.contains("<failure message=\"Element not found: text=Explore or description=Explore\">"));
+        assert!(xml.contains("<system-out>[[ATTACHMENT|error_screenshot.png]]</system-out>"));
+        assert!(xml.contains("<testcase name=\"Tap on NEXT\" classname=\"flows/login.yml\" time=\"0.000\" file=\"flows/login.yml\" line=\"9\">"));
+        assert!(xml.contains("<skipped message=\"A previous step failed\"/>"));
+        assert!(xml.contains("<property name=\"device\" value=\"Pixel &lt;7&gt;\"/>"));
+        assert!(xml.trim_end().ends_with("</testsuites>"));
+    }
+
+    #[test]
+    fn test_junit_report_has_one_suite_per_flow() {
+        let mut login = RunResult::new("Android".to_string());
+        login.steps = vec![
+            StepResult::passed("Tap on Login".to_string(), Duration::from_millis(500)),
+            StepResult::passed(
+                "Assert Home visible".to_string(),
+                Duration::from_millis(250),
+            ),
+        ];
+        login.set_flow("flows/login.yml".to_string());
+        let mut search = RunResult::new("Android".to_string());
+        search.steps = vec![
+            StepResult::failed(
+                "Tap on Search".to_string(),
+                "Element not found".to_string(),
+                Duration::from_secs(2),
+                None,
+            ),
+            StepResult::skipped(
+                "Input text shoes".to_string(),
+                "A previous step failed".to_string(),
+            ),
+        ];
+        search.set_flow("flows/search.yml".to_string());
+
+        let mut run = RunResult::new("Android".to_string());
+        run.merge(login);
+        run.merge(search);
+        let xml = JunitRenderer.render(&run).unwrap();
+
+        assert!(xml.contains(
+            "<testsuites name=\"rust_pilot\" tests=\"4\" failures=\"1\" errors=\"0\" skipped=\"1\""
+        ));
+        assert!(xml.contains("<testsuite name=\"flows/login.yml\" tests=\"2\" failures=\"0\" errors=\"0\" skipped=\"0\" time=\"0.750\""));
+        assert!(xml.contains("<testsuite name=\"flows/search.yml\" tests=\"2\" failures=\"1\" errors=\"0\" skipped=\"1\" time=\"2.000\""));
+        assert!(xml.contains("<testcase name=\"Tap on Search\" classname=\"flows/search.yml\""));
+        assert_eq!(xml.matches("</testsuite>").count(), 2);
+    }
+
+    #[test]
+    fn test_junit_report_for_run_that_could_not_start() {
+        let mut run = RunResult::new("iOS".to_string());
+        run.flows = vec!["login.yml".to_string()];
+        run.set_error("Connection error: refused".to_string());
+
+        let xml = JunitRenderer.render(&run).unwrap();
+
+        assert!(xml.contains("tests=\"1\" failures=\"0\" errors=\"1\""));
+        assert!(xml.contains("<error message=\"Connection error: refused\">"));
+    }
+
+    #[test]
+    fn test_report_format_parse() {
+        assert_eq!(ReportFormat::parse("junit").unwrap(), ReportFormat::Junit);
+        assert_eq!(ReportFormat::parse(" MD ").unwrap(), ReportFormat::Markdown);
+        assert_eq!(ReportFormat::parse("json").unwrap(), ReportFormat::Json);
+        assert_eq!(ReportFormat::Junit.renderer().extension(), "xml");
+        assert!(ReportFormat::parse("pdf").is_err());
+    }
+
+    #[test]
+    fn test_markdown_report_has_no_missing_logo_and_shows_flow() {
+        let mut run = sample_run("Android");
+        run.flow_graph = Some("idRoot0(flows) --> idStepName1(TapOn)\n".to_string());
+
+        let markdown = MarkdownRenderer.render(&run).unwrap();
+
+        assert!(!markdown.contains("logo.webp"));
+        assert!(markdown.contains("```mermaid\ngraph TD\nidRoot0(flows) --> idStepName1(TapOn)\n```"));
+    }
+
+    #[test]
+    fn test_html_report_inlines_screenshots_and_flow_tree() {
+        let screenshot_path = "test_html_report_screenshot.png";
+        fs::write(screenshot_path, b"png bytes").expect("Failed to write screenshot");
+
+        let mut run = sample_run("Android");
+        run.status = RunStatus::Failed;
+        run.steps.push(StepResult::failed(
+            "Tap on <Login>".to_string(),
+            "Element not found".to_string(),
+            Duration::from_secs(1),
+            Some(screenshot_path.to_string()),
+        ));
+        run.steps.push(StepResult::failed(
+            "Tap on Missing".to_string(),
+            "Element not found".to_string(),
+            Duration::from_secs(1),
+            Some("does_not_exist.png".to_string()),
+        ));
+        run.flow_graph = Some(
+            "idRoot0(flows) --> idRunFlow1(login.yml)\n\
+             idRunFlow1(login.yml) --> idStepName2(TapOn)\n\
+             idRoot0(flows) --> idStepName3(AssertVisible)\n"
+                .to_string(),
+        );
+
+        let html = HtmlRenderer.render(&run).unwrap();
+        fs::remove_file(screenshot_path).expect("Failed to clean up screenshot");
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<span class=\"badge failed\">FAILED</span>"));
+        assert!(html.contains("<li class=\"failed\"><span class=\"badge\">FAILED</span> 6. Tap on &lt;Login&gt;"));
+        assert!(html.contains("src=\"data:image/png;base64,cG5nIGJ5dGVz\""));
+        assert!(html.contains("<a href=\"does_not_exist.png\">"));
+        assert!(html.contains(
+            "<li><span class=\"flow-file\">login.yml</span>\n<ul class=\"flow\">\n<li>TapOn</li>\n</ul></li>\n<li>AssertVisible</li>"
+        ));
+        assert!(!html.contains("<script"));
+    }
+
+    #[test]
+    fn test_screenshot_paths_are_relative_to_the_report_dir() {
+        let dir = std::env::temp_dir().join("rust_pilot_relative_screenshots");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("screenshots")).unwrap();
+        fs::write(dir.join("screenshots/login_001.png"), b"png bytes").unwrap();
+
+        let mut run = sample_run("Android");
+        run.steps.push(StepResult::failed(
+            "Tap on Login".to_string(),
+            "Element not found".to_string(),
+            Duration::from_secs(1),
+            Some("screenshots/login_001.png".to_string()),
+        ));
+        let report_dir = dir.display().to_string();
+        let html = save_report(&run, &HtmlRenderer, &report_dir).unwrap();
+        assert!(fs::read_to_string(html)
+            .unwrap()
+            .contains("src=\"data:image/png;base64,cG5nIGJ5dGVz\""));
+        let markdown = MarkdownRenderer.render(&run).unwrap();
+        assert!(markdown.contains("[screenshot](screenshots/login_001.png)"));
+
+        // Reports rendered elsewhere point back at the original directory
+        run.rebase_artifacts(&dir);
+        let screenshot = run.steps.last().unwrap().screenshots().next().unwrap();
+        assert_eq!(fs::read(screenshot).unwrap(), b"png bytes");
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
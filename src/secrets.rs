@@ -0,0 +1,69 @@
+//! Secret values, masked out of every log line and report
+//!
+//! A variable is secret when its name starts with `SECRET_` or is listed in the
+//! `secrets` of a `rust_pilot.toml` profile. Its value is registered when it is
+//! interpolated, as is the text of an `inputText` with `secret: true`.
+
+use std::collections::BTreeSet;
+use std::sync::RwLock;
+
+use serde_json::Value;
+
+/// Shown in place of a secret value
+pub const MASK: &str = "***";
+/// Variables named with this prefix are always secret
+pub const SECRET_PREFIX: &str = "SECRET_";
+
+static SECRET_NAMES: RwLock<BTreeSet<String>> = RwLock::new(BTreeSet::new());
+/// Longest first, so a secret containing another one is masked whole
+static SECRET_VALUES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Marks the variable `name` as secret
+pub fn register_name(name: &str) {
+    SECRET_NAMES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .insert(name.to_string());
+}
+
+pub fn is_secret_name(name: &str) -> bool {
+    name.starts_with(SECRET_PREFIX)
+        || SECRET_NAMES
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(name)
+}
+
+/// Masks `value` from now on; empty values are ignored
+pub fn register_value(value: &str) {
+    if value.is_empty() {
+        return;
+    }
+    let mut values = SECRET_VALUES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if !values.iter().any(|known| known == value) {
+        values.push(value.to_string());
+        values.sort_by_key(|known| std::cmp::Reverse(known.len()));
+    }
+}
+
+/// `text` with every registered secret replaced by [`MASK`]
+pub fn mask(text: &str) -> String {
+    let values = SECRET_VALUES
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    values.iter().fold(text.to_string(), |text, value| {
+        text.replace(value.as_str(), MASK)
+    })
+}
+
+/// Masks every string inside a JSON value
+pub fn mask_value(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = mask(text),
+        Value::Array(values) => values.iter_mut().for_each(mask_value),
+        Value::Object(map) => map.values_mut().for_each(mask_value),
+        _ => {}
+    }
+}
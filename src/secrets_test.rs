@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::secrets::{is_secret_name, mask, mask_value, register_name, register_value, MASK};
+
+    #[test]
+    fn test_secret_names() {
+        assert!(is_secret_name("SECRET_PASSWORD"));
+        assert!(!is_secret_name("SECRETS_TEST_EMAIL"));
+        register_name("SECRETS_TEST_EMAIL");
+        assert!(is_secret_name("SECRETS_TEST_EMAIL"));
+    }
+
+    #[test]
+    fn test_mask_longest_secret_first() {
+        register_value("masked-abc");
+        register_value("masked-abc-def");
+        register_value("");
+        assert_eq!(
+            mask("Inserted masked-abc-def and masked-abc"),
+            format!("Inserted {} and {}", MASK, MASK)
+        );
+        assert_eq!(mask("nothing to hide"), "nothing to hide");
+    }
+
+    #[test]
+    fn test_mask_value() {
+        register_value("masked-json-4242");
+        let mut value = json!({
+            "description": "Input text masked-json-4242",
+            "steps": [{ "error": "masked-json-4242 rejected", "duration_ms": 4242 }],
+        });
+        mask_value(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "description": "Input text ***",
+                "steps": [{ "error": "*** rejected", "duration_ms": 4242 }],
+            })
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Test suites: every flow file under a directory
+//!
+//! Files that another flow of the directory includes with `runFlow` are not run
+//! on their own, and YAML files without an `appId` header, such as caps files
+//! or CI configs, are skipped. Flows run in path order.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::common::models::Step;
+use crate::common::utils::{get_content, parse_test_file};
+use crate::error::{Result, RustPilotError};
+use crate::logger::Logger;
+
+/// Extensions of flow files
+const FLOW_EXTENSIONS: [&str; 2] = ["yml", "yaml"];
+
+/// Top-level flows under `dir`, sorted by path
+///
+/// Files in `exclude`, such as a YAML caps file kept next to the flows, are ignored.
+pub fn discover_flows(dir: &Path, exclude: &[&Path]) -> Result<Vec<PathBuf>> {
+    let exclude: HashSet<PathBuf> = exclude
+        .iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .collect();
+    let mut files = Vec::new();
+    collect_flow_files(dir, &mut files)?;
+    files.retain(|file| {
+        file.canonicalize()
+            .map_or(true, |path| !exclude.contains(&path))
+    });
+    files.sort();
+    let mut flows = Vec::with_capacity(files.len());
+    for file in files {
+        if has_flow_header(&get_content(&file)?) {
+            flows.push(file);
+        } else {
+            Logger::warning(format!(
+                "Skipping {}: not a flow, it has no appId header",
+                file.display()
+            ));
+        }
+    }
+    let mut files = flows;
+
+    let mut included = HashSet::new();
+    for file in &files {
+        let (_, steps) = parse_test_file(file)?;
+        let flow_dir = file.parent().unwrap_or(Path::new(""));
+        for step in &steps {
+            if let Step::RunFlow { runFlow } = step {
+                // Paths built from variables are only known at run time
+                if runFlow.file().contains("${") {
+                    continue;
+                }
+                if let Ok(path) = flow_dir.join(runFlow.file()).canonicalize() {
+                    included.insert(path);
+                }
+            }
+        }
+    }
+
+    files.retain(|file| {
+        file.canonicalize()
+            .map_or(true, |path| !included.contains(&path))
+    });
+    Ok(files)
+}
+
+fn collect_flow_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let entries = fs::read_dir(dir).map_err(|e| RustPilotError::io(dir, e))?;
+    for entry in entries {
+        let path = entry.map_err(|e| RustPilotError::io(dir, e))?.path();
+        if path.is_dir() {
+            collect_flow_files(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .is_some_and(|extension| FLOW_EXTENSIONS.contains(&extension))
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Whether the first YAML document of `content` is a flow header, with an `appId`
+fn has_flow_header(content: &str) -> bool {
+    serde_yaml::Deserializer::from_str(content)
+        .next()
+        .and_then(|document| serde_yaml::Value::deserialize(document).ok())
+        .is_some_and(|header| header.get("appId").is_some())
+}
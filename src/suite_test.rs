@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use crate::error::RustPilotError;
+    use crate::suite::discover_flows;
+
+    fn write(dir: &Path, name: &str, contents: &str) {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).expect("Failed to create flow directory");
+        fs::write(path, contents).expect("Failed to write flow");
+    }
+
+    #[test]
+    fn test_discover_flows_skips_included_flows() {
+        let dir = std::env::temp_dir().join("rust_pilot_suite_discovery");
+        let _ = fs::remove_dir_all(&dir);
+        write(
+            &dir,
+            "b-checkout.yml",
+            "appId: app\n---\n- runFlow: login/login.yml\n",
+        );
+        write(
+            &dir,
+            "a-search.yaml",
+            "appId: app\ntags:\n  - smoke\n---\n- tapOn: Search\n",
+        );
+        write(
+            &dir,
+            "login/login.yml",
+            "appId: app\n---\n- runFlow:\n    file: otp.yml\n",
+        );
+        write(
+            &dir,
+            "login/otp.yml",
+            "appId: app\n---\n- inputText: \"1234\"\n",
+        );
+        write(&dir, "caps.yml", "platform: android\n");
+        write(&dir, "notes.txt", "not a flow");
+
+        let flows = discover_flows(&dir, &[&dir.join("caps.yml")]);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(
+            flows.unwrap(),
+            vec![dir.join("a-search.yaml"), dir.join("b-checkout.yml")]
+        );
+    }
+
+    #[test]
+    fn test_discover_flows_reports_invalid_flows() {
+        let dir = std::env::temp_dir().join("rust_pilot_suite_invalid");
+        let _ = fs::remove_dir_all(&dir);
+        write(&dir, "broken.yml", "appId: app\n");
+
+        let flows = discover_flows(&dir, &[]);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(matches!(flows, Err(RustPilotError::Parse { .. })));
+        assert!(matches!(
+            discover_flows(&dir, &[]),
+            Err(RustPilotError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_discover_flows_skips_yaml_that_is_not_a_flow() {
+        let dir = std::env::temp_dir().join("rust_pilot_suite_not_flows");
+        let _ = fs::remove_dir_all(&dir);
+        write(&dir, "login.yml", "appId: app\n---\n- tapOn: Login\n");
+        write(&dir, "caps.yaml", "platform: android\napp_path: app.apk\n");
+        write(
+            &dir,
+            ".ci/pipeline.yml",
+            "on: push\njobs:\n  test:\n    steps: []\n",
+        );
+        write(&dir, "bad-syntax.yml", "key: [unclosed\n");
+        write(&dir, "empty.yml", "");
+
+        let flows = discover_flows(&dir, &[]);
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(flows.unwrap(), vec![dir.join("login.yml")]);
+    }
+}